@@ -0,0 +1,87 @@
+use lib::int_code::machine::Machine;
+use lib::int_code::read_file;
+use std::time::{Duration, Instant};
+
+// The same program compiled by intcode-compile, regenerated with
+//
+//     cargo run --bin intcode-compile fixtures/day9.intcode > fixtures/boost.rs
+#[path = "../fixtures/boost.rs"]
+mod boost;
+
+// Compares the interpreter with the decode cache turned off and on, and the compiled program,
+// on the day 9 BOOST program in sensor boost mode, which runs a few hundred thousand
+// instructions. The run with the cache off is the baseline each speed-up is measured against.
+// It is still the current interpreter, not the one from before the cache was added.
+//
+//     cargo bench --bench interpreter [runs]
+static RUNS: usize = 20;
+
+struct Buffers(Vec<i64>, Vec<i64>);
+
+impl boost::Io for Buffers {
+    fn input(&mut self) -> Option<i64> {
+        self.0.pop()
+    }
+
+    fn output(&mut self, value: i64) {
+        self.1.push(value);
+    }
+}
+
+fn run_compiled() -> (Duration, Vec<i64>) {
+    let start = Instant::now();
+    let mut buffers = Buffers(vec![2], Vec::new());
+    boost::run(&mut buffers).expect("failed to run");
+    (start.elapsed(), buffers.1)
+}
+
+fn run(program: &[i64], cached: bool) -> (Duration, Vec<i64>) {
+    let start = Instant::now();
+    let mut machine = Machine::new(program.to_vec(), vec![2], Vec::new()).with_decode_cache(cached);
+    machine.execute().expect("failed to execute");
+    (start.elapsed(), machine.into_output())
+}
+
+fn main() -> std::io::Result<()> {
+    let program = read_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/day9.intcode"
+    ))?;
+    // cargo passes `--bench` to custom harnesses, so only numeric arguments are used.
+    let runs = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok().filter(|runs| *runs > 0))
+        .unwrap_or(RUNS);
+
+    let (_, expected) = run(&program, false);
+    let mut baseline = None;
+    for (name, cached) in [
+        ("cache off", Some(false)),
+        ("cache on", Some(true)),
+        ("compiled", None),
+    ] {
+        let mut times = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let (time, output) = match cached {
+                Some(cached) => run(&program, cached),
+                None => run_compiled(),
+            };
+            assert_eq!(output, expected, "{} output differs", name);
+            times.push(time);
+        }
+        times.sort();
+        let total: Duration = times.iter().sum();
+        let median = times[runs / 2];
+        let baseline = *baseline.get_or_insert(median);
+        println!(
+            "{:<9} runs: {} mean: {:?} median: {:?} min: {:?} speed-up: {:.1}x",
+            name,
+            runs,
+            total / runs as u32,
+            median,
+            times[0],
+            baseline.as_secs_f64() / median.as_secs_f64()
+        );
+    }
+    Ok(())
+}
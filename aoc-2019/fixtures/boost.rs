@@ -0,0 +1,998 @@
+// Generated by intcode-compile from a program of 973 values. Do not edit.
+#![cfg_attr(rustfmt, rustfmt::skip)]
+#![allow(clippy::all, dead_code, unused_mut, unused_parens, unused_variables)]
+
+pub trait Io {
+    fn input(&mut self) -> Option<i64>;
+    fn output(&mut self, value: i64);
+}
+
+// Each error carries the address of the instruction that failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    InputClosed(usize),
+    NegativeAddress(usize, i64),
+    // The program wrote to one of its reachable instructions.
+    CodeWrite(usize, usize),
+    // The block at the address was about to run after the program had written over it.
+    ModifiedCode(usize),
+    Overflow(usize),
+    // Execution continued somewhere that isn't a compiled block.
+    BadJump(i64),
+}
+
+// Also whether the program has written to any unreached code.
+struct Memory(Vec<i64>, bool);
+
+impl Memory {
+    #[inline]
+    fn read(&self, at: usize, address: i64) -> Result<i64, Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        Ok(self.0.get(address as usize).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    fn write(&mut self, at: usize, address: i64, value: i64) -> Result<(), Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        let address = address as usize;
+        if is_code(address) {
+            return Err(Error::CodeWrite(at, address));
+        }
+        if is_unreached(address) {
+            self.1 = true;
+        }
+        if address >= self.0.len() {
+            self.0.resize(address + 1, 0);
+        }
+        self.0[address] = value;
+        Ok(())
+    }
+
+    #[inline]
+    fn check(&self, start: usize, end: usize) -> Result<(), Error> {
+        if self.1 && self.0[start..end] != PROGRAM[start..end] {
+            return Err(Error::ModifiedCode(start));
+        }
+        Ok(())
+    }
+}
+
+fn is_code(address: usize) -> bool {
+    matches!(address, 0..=62 | 65..=335 | 904..=914 | 922..=941 | 964..=972)
+}
+
+fn is_unreached(address: usize) -> bool {
+    matches!(address, 336..=903 | 915..=921 | 942..=963)
+}
+
+static PROGRAM: [i64; 973] = [1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1101, 0, 3, 1000, 109, 988, 209, 12, 9, 1000, 209, 6, 209, 3, 203, 0, 1008, 1000, 1, 63, 1005, 63, 65, 1008, 1000, 2, 63, 1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58, 4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17, 104, 0, 99, 0, 0, 1102, 1, 39, 1013, 1102, 1, 21, 1018, 1101, 0, 336, 1027, 1102, 1, 38, 1012, 1101, 534, 0, 1025, 1101, 539, 0, 1024, 1101, 0, 380, 1023, 1102, 1, 23, 1014, 1102, 29, 1, 1000, 1102, 24, 1, 1019, 1102, 1, 28, 1011, 1101, 339, 0, 1026, 1101, 31, 0, 1005, 1102, 36, 1, 1017, 1102, 26, 1, 1007, 1102, 1, 407, 1028, 1101, 387, 0, 1022, 1101, 0, 30, 1001, 1101, 34, 0, 1010, 1102, 1, 32, 1006, 1101, 0, 1, 1021, 1102, 27, 1, 1008, 1102, 22, 1, 1004, 1102, 1, 20, 1015, 1101, 0, 37, 1016, 1101, 0, 0, 1020, 1102, 1, 398, 1029, 1101, 25, 0, 1009, 1101, 0, 35, 1003, 1101, 33, 0, 1002, 109, 27, 1206, -6, 197, 1001, 64, 1, 64, 1105, 1, 199, 4, 187, 1002, 64, 2, 64, 109, -22, 2107, 26, 3, 63, 1005, 63, 217, 4, 205, 1105, 1, 221, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 17, 21107, 40, 39, -8, 1005, 1014, 241, 1001, 64, 1, 64, 1105, 1, 243, 4, 227, 1002, 64, 2, 64, 109, -8, 1206, 6, 261, 4, 249, 1001, 64, 1, 64, 1106, 0, 261, 1002, 64, 2, 64, 109, -7, 2108, 24, 0, 63, 1005, 63, 281, 1001, 64, 1, 64, 1105, 1, 283, 4, 267, 1002, 64, 2, 64, 109, 11, 21102, 41, 1, -3, 1008, 1015, 42, 63, 1005, 63, 303, 1105, 1, 309, 4, 289, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 1, 1205, 2, 327, 4, 315, 1001, 64, 1, 64, 1105, 1, 327, 1002, 64, 2, 64, 109, 10, 2106, 0, -2, 1106, 0, 345, 4, 333, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -15, 21102, 42, 1, 3, 1008, 1017, 42, 63, 1005, 63, 367, 4, 351, 1105, 1, 371, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -1, 2105, 1, 10, 1001, 64, 1, 64, 1105, 1, 389, 4, 377, 1002, 64, 2, 64, 109, 24, 2106, 0, -9, 4, 395, 1001, 64, 1, 64, 1105, 1, 407, 1002, 64, 2, 64, 109, -30, 1208, -2, 32, 63, 1005, 63, 427, 1001, 64, 1, 64, 1106, 0, 429, 4, 413, 1002, 64, 2, 64, 109, 2, 1201, 0, 0, 63, 1008, 63, 27, 63, 1005, 63, 449, 1106, 0, 455, 4, 435, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 5, 21107, 43, 44, 0, 1005, 1014, 473, 4, 461, 1106, 0, 477, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -16, 1202, 3, 1, 63, 1008, 63, 33, 63, 1005, 63, 501, 1001, 64, 1, 64, 1106, 0, 503, 4, 483, 1002, 64, 2, 64, 109, 10, 1207, -4, 21, 63, 1005, 63, 523, 1001, 64, 1, 64, 1106, 0, 525, 4, 509, 1002, 64, 2, 64, 109, 11, 2105, 1, 5, 4, 531, 1106, 0, 543, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -8, 21101, 44, 0, 5, 1008, 1016, 47, 63, 1005, 63, 563, 1106, 0, 569, 4, 549, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -13, 2102, 1, 8, 63, 1008, 63, 34, 63, 1005, 63, 593, 1001, 64, 1, 64, 1105, 1, 595, 4, 575, 1002, 64, 2, 64, 109, 8, 1208, -1, 31, 63, 1005, 63, 617, 4, 601, 1001, 64, 1, 64, 1106, 0, 617, 1002, 64, 2, 64, 109, -8, 2108, 33, 4, 63, 1005, 63, 635, 4, 623, 1105, 1, 639, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 10, 1202, -1, 1, 63, 1008, 63, 26, 63, 1005, 63, 665, 4, 645, 1001, 64, 1, 64, 1105, 1, 665, 1002, 64, 2, 64, 109, -9, 2107, 30, 1, 63, 1005, 63, 685, 1001, 64, 1, 64, 1105, 1, 687, 4, 671, 1002, 64, 2, 64, 109, 25, 1205, -4, 703, 1001, 64, 1, 64, 1105, 1, 705, 4, 693, 1002, 64, 2, 64, 109, -19, 2101, 0, -5, 63, 1008, 63, 26, 63, 1005, 63, 725, 1105, 1, 731, 4, 711, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 6, 1207, -2, 26, 63, 1005, 63, 749, 4, 737, 1105, 1, 753, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -10, 21108, 45, 46, 9, 1005, 1010, 769, 1105, 1, 775, 4, 759, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -10, 1201, 10, 0, 63, 1008, 63, 30, 63, 1005, 63, 801, 4, 781, 1001, 64, 1, 64, 1106, 0, 801, 1002, 64, 2, 64, 109, 21, 21108, 46, 46, 3, 1005, 1015, 819, 4, 807, 1106, 0, 823, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -4, 2102, 1, -3, 63, 1008, 63, 31, 63, 1005, 63, 849, 4, 829, 1001, 64, 1, 64, 1106, 0, 849, 1002, 64, 2, 64, 109, -5, 2101, 0, 1, 63, 1008, 63, 22, 63, 1005, 63, 875, 4, 855, 1001, 64, 1, 64, 1105, 1, 875, 1002, 64, 2, 64, 109, 17, 21101, 47, 0, -3, 1008, 1017, 47, 63, 1005, 63, 897, 4, 881, 1105, 1, 901, 1001, 64, 1, 64, 4, 64, 99, 21101, 0, 27, 1, 21102, 1, 915, 0, 1105, 1, 922, 21201, 1, 38480, 1, 204, 1, 99, 109, 3, 1207, -2, 3, 63, 1005, 63, 964, 21201, -2, -1, 1, 21101, 0, 942, 0, 1106, 0, 922, 21202, 1, 1, -1, 21201, -2, -3, 1, 21101, 957, 0, 0, 1105, 1, 922, 22201, 1, -1, -2, 1106, 0, 968, 22101, 0, -2, -2, 109, -3, 2105, 1, 0];
+
+pub fn run<T: Io>(io: &mut T) -> Result<(), Error> {
+    let mut memory = Memory(PROGRAM.to_vec(), false);
+    let mut rb: i64 = 0;
+    let mut block: i64 = 0;
+    loop {
+        block = match block {
+            0 => {
+                // 0: mul #34463338, #34463338, [63]
+                memory.0[63] = i64::checked_mul((34463338), (34463338)).ok_or(Error::Overflow(0))?;
+                // 4: lt [63], #34463338, [63]
+                memory.0[63] = (memory.0[63] < (34463338)) as i64;
+                // 8: jt [63], #53
+                if memory.0[63] != 0 { (53) } else { 11 }
+            }
+            11 => {
+                // 11: add #0, #3, [1000]
+                memory.write(11, 1000, i64::checked_add((0), (3)).ok_or(Error::Overflow(11))?)?;
+                // 15: arb #988
+                rb = i64::checked_add(rb, (988)).ok_or(Error::Overflow(15))?;
+                // 17: arb rb+12
+                rb = i64::checked_add(rb, memory.read(17, rb + (12))?).ok_or(Error::Overflow(17))?;
+                // 19: arb [1000]
+                rb = i64::checked_add(rb, memory.read(19, 1000)?).ok_or(Error::Overflow(19))?;
+                // 21: arb rb+6
+                rb = i64::checked_add(rb, memory.read(21, rb + (6))?).ok_or(Error::Overflow(21))?;
+                // 23: arb rb+3
+                rb = i64::checked_add(rb, memory.read(23, rb + (3))?).ok_or(Error::Overflow(23))?;
+                // 25: in rb+0
+                memory.write(25, rb + (0), io.input().ok_or(Error::InputClosed(25))?)?;
+                // 27: eq [1000], #1, [63]
+                memory.0[63] = (memory.read(27, 1000)? == (1)) as i64;
+                // 31: jt [63], #65
+                if memory.0[63] != 0 { (65) } else { 34 }
+            }
+            34 => {
+                // 34: eq [1000], #2, [63]
+                memory.0[63] = (memory.read(34, 1000)? == (2)) as i64;
+                // 38: jt [63], #904
+                if memory.0[63] != 0 { (904) } else { 41 }
+            }
+            41 => {
+                // 41: eq [1000], #0, [63]
+                memory.0[63] = (memory.read(41, 1000)? == (0)) as i64;
+                // 45: jt [63], #58
+                if memory.0[63] != 0 { (58) } else { 48 }
+            }
+            48 => {
+                // 48: out [25]
+                io.output(memory.0[25]);
+                // 50: out #0
+                io.output((0));
+                // 52: hlt
+                return Ok(());
+            }
+            53 => {
+                // 53: out [0]
+                io.output(memory.0[0]);
+                // 55: out #0
+                io.output((0));
+                // 57: hlt
+                return Ok(());
+            }
+            58 => {
+                // 58: out [17]
+                io.output(memory.0[17]);
+                // 60: out #0
+                io.output((0));
+                // 62: hlt
+                return Ok(());
+            }
+            65 => {
+                // 65: mul #1, #39, [1013]
+                memory.write(65, 1013, i64::checked_mul((1), (39)).ok_or(Error::Overflow(65))?)?;
+                // 69: mul #1, #21, [1018]
+                memory.write(69, 1018, i64::checked_mul((1), (21)).ok_or(Error::Overflow(69))?)?;
+                // 73: add #0, #336, [1027]
+                memory.write(73, 1027, i64::checked_add((0), (336)).ok_or(Error::Overflow(73))?)?;
+                // 77: mul #1, #38, [1012]
+                memory.write(77, 1012, i64::checked_mul((1), (38)).ok_or(Error::Overflow(77))?)?;
+                // 81: add #534, #0, [1025]
+                memory.write(81, 1025, i64::checked_add((534), (0)).ok_or(Error::Overflow(81))?)?;
+                // 85: add #539, #0, [1024]
+                memory.write(85, 1024, i64::checked_add((539), (0)).ok_or(Error::Overflow(85))?)?;
+                // 89: add #0, #380, [1023]
+                memory.write(89, 1023, i64::checked_add((0), (380)).ok_or(Error::Overflow(89))?)?;
+                // 93: mul #1, #23, [1014]
+                memory.write(93, 1014, i64::checked_mul((1), (23)).ok_or(Error::Overflow(93))?)?;
+                // 97: mul #29, #1, [1000]
+                memory.write(97, 1000, i64::checked_mul((29), (1)).ok_or(Error::Overflow(97))?)?;
+                // 101: mul #24, #1, [1019]
+                memory.write(101, 1019, i64::checked_mul((24), (1)).ok_or(Error::Overflow(101))?)?;
+                // 105: mul #1, #28, [1011]
+                memory.write(105, 1011, i64::checked_mul((1), (28)).ok_or(Error::Overflow(105))?)?;
+                // 109: add #339, #0, [1026]
+                memory.write(109, 1026, i64::checked_add((339), (0)).ok_or(Error::Overflow(109))?)?;
+                // 113: add #31, #0, [1005]
+                memory.write(113, 1005, i64::checked_add((31), (0)).ok_or(Error::Overflow(113))?)?;
+                // 117: mul #36, #1, [1017]
+                memory.write(117, 1017, i64::checked_mul((36), (1)).ok_or(Error::Overflow(117))?)?;
+                // 121: mul #26, #1, [1007]
+                memory.write(121, 1007, i64::checked_mul((26), (1)).ok_or(Error::Overflow(121))?)?;
+                // 125: mul #1, #407, [1028]
+                memory.write(125, 1028, i64::checked_mul((1), (407)).ok_or(Error::Overflow(125))?)?;
+                // 129: add #387, #0, [1022]
+                memory.write(129, 1022, i64::checked_add((387), (0)).ok_or(Error::Overflow(129))?)?;
+                // 133: add #0, #30, [1001]
+                memory.write(133, 1001, i64::checked_add((0), (30)).ok_or(Error::Overflow(133))?)?;
+                // 137: add #34, #0, [1010]
+                memory.write(137, 1010, i64::checked_add((34), (0)).ok_or(Error::Overflow(137))?)?;
+                // 141: mul #1, #32, [1006]
+                memory.write(141, 1006, i64::checked_mul((1), (32)).ok_or(Error::Overflow(141))?)?;
+                // 145: add #0, #1, [1021]
+                memory.write(145, 1021, i64::checked_add((0), (1)).ok_or(Error::Overflow(145))?)?;
+                // 149: mul #27, #1, [1008]
+                memory.write(149, 1008, i64::checked_mul((27), (1)).ok_or(Error::Overflow(149))?)?;
+                // 153: mul #22, #1, [1004]
+                memory.write(153, 1004, i64::checked_mul((22), (1)).ok_or(Error::Overflow(153))?)?;
+                // 157: mul #1, #20, [1015]
+                memory.write(157, 1015, i64::checked_mul((1), (20)).ok_or(Error::Overflow(157))?)?;
+                // 161: add #0, #37, [1016]
+                memory.write(161, 1016, i64::checked_add((0), (37)).ok_or(Error::Overflow(161))?)?;
+                // 165: add #0, #0, [1020]
+                memory.write(165, 1020, i64::checked_add((0), (0)).ok_or(Error::Overflow(165))?)?;
+                // 169: mul #1, #398, [1029]
+                memory.write(169, 1029, i64::checked_mul((1), (398)).ok_or(Error::Overflow(169))?)?;
+                // 173: add #25, #0, [1009]
+                memory.write(173, 1009, i64::checked_add((25), (0)).ok_or(Error::Overflow(173))?)?;
+                // 177: add #0, #35, [1003]
+                memory.write(177, 1003, i64::checked_add((0), (35)).ok_or(Error::Overflow(177))?)?;
+                // 181: add #33, #0, [1002]
+                memory.write(181, 1002, i64::checked_add((33), (0)).ok_or(Error::Overflow(181))?)?;
+                // 185: arb #27
+                rb = i64::checked_add(rb, (27)).ok_or(Error::Overflow(185))?;
+                // 187: jf rb-6, #197
+                if memory.read(187, rb + (-6))? == 0 { (197) } else { 190 }
+            }
+            190 => {
+                // 190: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(190))?;
+                // 194: jt #1, #199
+                if (1) != 0 { (199) } else { 197 }
+            }
+            197 => {
+                // 197: out [187]
+                io.output(memory.0[187]);
+                199
+            }
+            199 => {
+                // 199: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(199))?;
+                // 203: arb #-22
+                rb = i64::checked_add(rb, (-22)).ok_or(Error::Overflow(203))?;
+                // 205: lt #26, rb+3, [63]
+                memory.0[63] = ((26) < memory.read(205, rb + (3))?) as i64;
+                // 209: jt [63], #217
+                if memory.0[63] != 0 { (217) } else { 212 }
+            }
+            212 => {
+                // 212: out [205]
+                io.output(memory.0[205]);
+                // 214: jt #1, #221
+                if (1) != 0 { (221) } else { 217 }
+            }
+            217 => {
+                // 217: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(217))?;
+                221
+            }
+            221 => {
+                // 221: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(221))?;
+                // 225: arb #17
+                rb = i64::checked_add(rb, (17)).ok_or(Error::Overflow(225))?;
+                // 227: lt #40, #39, rb-8
+                memory.write(227, rb + (-8), ((40) < (39)) as i64)?;
+                // 231: jt [1014], #241
+                if memory.read(231, 1014)? != 0 { (241) } else { 234 }
+            }
+            234 => {
+                // 234: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(234))?;
+                // 238: jt #1, #243
+                if (1) != 0 { (243) } else { 241 }
+            }
+            241 => {
+                // 241: out [227]
+                io.output(memory.0[227]);
+                243
+            }
+            243 => {
+                // 243: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(243))?;
+                // 247: arb #-8
+                rb = i64::checked_add(rb, (-8)).ok_or(Error::Overflow(247))?;
+                // 249: jf rb+6, #261
+                if memory.read(249, rb + (6))? == 0 { (261) } else { 252 }
+            }
+            252 => {
+                // 252: out [249]
+                io.output(memory.0[249]);
+                // 254: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(254))?;
+                // 258: jf #0, #261
+                if (0) == 0 { (261) } else { 261 }
+            }
+            261 => {
+                // 261: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(261))?;
+                // 265: arb #-7
+                rb = i64::checked_add(rb, (-7)).ok_or(Error::Overflow(265))?;
+                // 267: eq #24, rb+0, [63]
+                memory.0[63] = ((24) == memory.read(267, rb + (0))?) as i64;
+                // 271: jt [63], #281
+                if memory.0[63] != 0 { (281) } else { 274 }
+            }
+            274 => {
+                // 274: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(274))?;
+                // 278: jt #1, #283
+                if (1) != 0 { (283) } else { 281 }
+            }
+            281 => {
+                // 281: out [267]
+                io.output(memory.0[267]);
+                283
+            }
+            283 => {
+                // 283: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(283))?;
+                // 287: arb #11
+                rb = i64::checked_add(rb, (11)).ok_or(Error::Overflow(287))?;
+                // 289: mul #41, #1, rb-3
+                memory.write(289, rb + (-3), i64::checked_mul((41), (1)).ok_or(Error::Overflow(289))?)?;
+                // 293: eq [1015], #42, [63]
+                memory.0[63] = (memory.read(293, 1015)? == (42)) as i64;
+                // 297: jt [63], #303
+                if memory.0[63] != 0 { (303) } else { 300 }
+            }
+            300 => {
+                // 300: jt #1, #309
+                if (1) != 0 { (309) } else { 303 }
+            }
+            303 => {
+                // 303: out [289]
+                io.output(memory.0[289]);
+                // 305: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(305))?;
+                309
+            }
+            309 => {
+                // 309: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(309))?;
+                // 313: arb #1
+                rb = i64::checked_add(rb, (1)).ok_or(Error::Overflow(313))?;
+                // 315: jt rb+2, #327
+                if memory.read(315, rb + (2))? != 0 { (327) } else { 318 }
+            }
+            318 => {
+                // 318: out [315]
+                io.output(memory.0[315]);
+                // 320: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(320))?;
+                // 324: jt #1, #327
+                if (1) != 0 { (327) } else { 327 }
+            }
+            327 => {
+                // 327: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(327))?;
+                // 331: arb #10
+                rb = i64::checked_add(rb, (10)).ok_or(Error::Overflow(331))?;
+                // 333: jf #0, rb-2
+                if (0) == 0 { memory.read(333, rb + (-2))? } else { 336 }
+            }
+            336 => {
+                memory.check(336, 339)?;
+                // 336: jf #0, #345
+                if (0) == 0 { (345) } else { 339 }
+            }
+            339 => {
+                memory.check(339, 345)?;
+                // 339: out [333]
+                io.output(memory.0[333]);
+                // 341: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(341))?;
+                345
+            }
+            345 => {
+                memory.check(345, 362)?;
+                // 345: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(345))?;
+                // 349: arb #-15
+                rb = i64::checked_add(rb, (-15)).ok_or(Error::Overflow(349))?;
+                // 351: mul #42, #1, rb+3
+                memory.write(351, rb + (3), i64::checked_mul((42), (1)).ok_or(Error::Overflow(351))?)?;
+                // 355: eq [1017], #42, [63]
+                memory.0[63] = (memory.read(355, 1017)? == (42)) as i64;
+                // 359: jt [63], #367
+                if memory.0[63] != 0 { (367) } else { 362 }
+            }
+            362 => {
+                memory.check(362, 367)?;
+                // 362: out [351]
+                io.output(memory.0[351]);
+                // 364: jt #1, #371
+                if (1) != 0 { (371) } else { 367 }
+            }
+            367 => {
+                memory.check(367, 371)?;
+                // 367: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(367))?;
+                371
+            }
+            371 => {
+                memory.check(371, 380)?;
+                // 371: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(371))?;
+                // 375: arb #-1
+                rb = i64::checked_add(rb, (-1)).ok_or(Error::Overflow(375))?;
+                // 377: jt #1, rb+10
+                if (1) != 0 { memory.read(377, rb + (10))? } else { 380 }
+            }
+            380 => {
+                memory.check(380, 387)?;
+                // 380: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(380))?;
+                // 384: jt #1, #389
+                if (1) != 0 { (389) } else { 387 }
+            }
+            387 => {
+                memory.check(387, 389)?;
+                // 387: out [377]
+                io.output(memory.0[377]);
+                389
+            }
+            389 => {
+                memory.check(389, 398)?;
+                // 389: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(389))?;
+                // 393: arb #24
+                rb = i64::checked_add(rb, (24)).ok_or(Error::Overflow(393))?;
+                // 395: jf #0, rb-9
+                if (0) == 0 { memory.read(395, rb + (-9))? } else { 398 }
+            }
+            398 => {
+                memory.check(398, 407)?;
+                // 398: out [395]
+                io.output(memory.0[395]);
+                // 400: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(400))?;
+                // 404: jt #1, #407
+                if (1) != 0 { (407) } else { 407 }
+            }
+            407 => {
+                memory.check(407, 420)?;
+                // 407: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(407))?;
+                // 411: arb #-30
+                rb = i64::checked_add(rb, (-30)).ok_or(Error::Overflow(411))?;
+                // 413: eq rb-2, #32, [63]
+                memory.0[63] = (memory.read(413, rb + (-2))? == (32)) as i64;
+                // 417: jt [63], #427
+                if memory.0[63] != 0 { (427) } else { 420 }
+            }
+            420 => {
+                memory.check(420, 427)?;
+                // 420: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(420))?;
+                // 424: jf #0, #429
+                if (0) == 0 { (429) } else { 427 }
+            }
+            427 => {
+                memory.check(427, 429)?;
+                // 427: out [413]
+                io.output(memory.0[413]);
+                429
+            }
+            429 => {
+                memory.check(429, 446)?;
+                // 429: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(429))?;
+                // 433: arb #2
+                rb = i64::checked_add(rb, (2)).ok_or(Error::Overflow(433))?;
+                // 435: add rb+0, #0, [63]
+                memory.0[63] = i64::checked_add(memory.read(435, rb + (0))?, (0)).ok_or(Error::Overflow(435))?;
+                // 439: eq [63], #27, [63]
+                memory.0[63] = (memory.0[63] == (27)) as i64;
+                // 443: jt [63], #449
+                if memory.0[63] != 0 { (449) } else { 446 }
+            }
+            446 => {
+                memory.check(446, 449)?;
+                // 446: jf #0, #455
+                if (0) == 0 { (455) } else { 449 }
+            }
+            449 => {
+                memory.check(449, 455)?;
+                // 449: out [435]
+                io.output(memory.0[435]);
+                // 451: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(451))?;
+                455
+            }
+            455 => {
+                memory.check(455, 468)?;
+                // 455: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(455))?;
+                // 459: arb #5
+                rb = i64::checked_add(rb, (5)).ok_or(Error::Overflow(459))?;
+                // 461: lt #43, #44, rb+0
+                memory.write(461, rb + (0), ((43) < (44)) as i64)?;
+                // 465: jt [1014], #473
+                if memory.read(465, 1014)? != 0 { (473) } else { 468 }
+            }
+            468 => {
+                memory.check(468, 473)?;
+                // 468: out [461]
+                io.output(memory.0[461]);
+                // 470: jf #0, #477
+                if (0) == 0 { (477) } else { 473 }
+            }
+            473 => {
+                memory.check(473, 477)?;
+                // 473: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(473))?;
+                477
+            }
+            477 => {
+                memory.check(477, 494)?;
+                // 477: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(477))?;
+                // 481: arb #-16
+                rb = i64::checked_add(rb, (-16)).ok_or(Error::Overflow(481))?;
+                // 483: mul rb+3, #1, [63]
+                memory.0[63] = i64::checked_mul(memory.read(483, rb + (3))?, (1)).ok_or(Error::Overflow(483))?;
+                // 487: eq [63], #33, [63]
+                memory.0[63] = (memory.0[63] == (33)) as i64;
+                // 491: jt [63], #501
+                if memory.0[63] != 0 { (501) } else { 494 }
+            }
+            494 => {
+                memory.check(494, 501)?;
+                // 494: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(494))?;
+                // 498: jf #0, #503
+                if (0) == 0 { (503) } else { 501 }
+            }
+            501 => {
+                memory.check(501, 503)?;
+                // 501: out [483]
+                io.output(memory.0[483]);
+                503
+            }
+            503 => {
+                memory.check(503, 516)?;
+                // 503: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(503))?;
+                // 507: arb #10
+                rb = i64::checked_add(rb, (10)).ok_or(Error::Overflow(507))?;
+                // 509: lt rb-4, #21, [63]
+                memory.0[63] = (memory.read(509, rb + (-4))? < (21)) as i64;
+                // 513: jt [63], #523
+                if memory.0[63] != 0 { (523) } else { 516 }
+            }
+            516 => {
+                memory.check(516, 523)?;
+                // 516: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(516))?;
+                // 520: jf #0, #525
+                if (0) == 0 { (525) } else { 523 }
+            }
+            523 => {
+                memory.check(523, 525)?;
+                // 523: out [509]
+                io.output(memory.0[509]);
+                525
+            }
+            525 => {
+                memory.check(525, 534)?;
+                // 525: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(525))?;
+                // 529: arb #11
+                rb = i64::checked_add(rb, (11)).ok_or(Error::Overflow(529))?;
+                // 531: jt #1, rb+5
+                if (1) != 0 { memory.read(531, rb + (5))? } else { 534 }
+            }
+            534 => {
+                memory.check(534, 539)?;
+                // 534: out [531]
+                io.output(memory.0[531]);
+                // 536: jf #0, #543
+                if (0) == 0 { (543) } else { 539 }
+            }
+            539 => {
+                memory.check(539, 543)?;
+                // 539: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(539))?;
+                543
+            }
+            543 => {
+                memory.check(543, 560)?;
+                // 543: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(543))?;
+                // 547: arb #-8
+                rb = i64::checked_add(rb, (-8)).ok_or(Error::Overflow(547))?;
+                // 549: add #44, #0, rb+5
+                memory.write(549, rb + (5), i64::checked_add((44), (0)).ok_or(Error::Overflow(549))?)?;
+                // 553: eq [1016], #47, [63]
+                memory.0[63] = (memory.read(553, 1016)? == (47)) as i64;
+                // 557: jt [63], #563
+                if memory.0[63] != 0 { (563) } else { 560 }
+            }
+            560 => {
+                memory.check(560, 563)?;
+                // 560: jf #0, #569
+                if (0) == 0 { (569) } else { 563 }
+            }
+            563 => {
+                memory.check(563, 569)?;
+                // 563: out [549]
+                io.output(memory.0[549]);
+                // 565: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(565))?;
+                569
+            }
+            569 => {
+                memory.check(569, 586)?;
+                // 569: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(569))?;
+                // 573: arb #-13
+                rb = i64::checked_add(rb, (-13)).ok_or(Error::Overflow(573))?;
+                // 575: mul #1, rb+8, [63]
+                memory.0[63] = i64::checked_mul((1), memory.read(575, rb + (8))?).ok_or(Error::Overflow(575))?;
+                // 579: eq [63], #34, [63]
+                memory.0[63] = (memory.0[63] == (34)) as i64;
+                // 583: jt [63], #593
+                if memory.0[63] != 0 { (593) } else { 586 }
+            }
+            586 => {
+                memory.check(586, 593)?;
+                // 586: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(586))?;
+                // 590: jt #1, #595
+                if (1) != 0 { (595) } else { 593 }
+            }
+            593 => {
+                memory.check(593, 595)?;
+                // 593: out [575]
+                io.output(memory.0[575]);
+                595
+            }
+            595 => {
+                memory.check(595, 608)?;
+                // 595: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(595))?;
+                // 599: arb #8
+                rb = i64::checked_add(rb, (8)).ok_or(Error::Overflow(599))?;
+                // 601: eq rb-1, #31, [63]
+                memory.0[63] = (memory.read(601, rb + (-1))? == (31)) as i64;
+                // 605: jt [63], #617
+                if memory.0[63] != 0 { (617) } else { 608 }
+            }
+            608 => {
+                memory.check(608, 617)?;
+                // 608: out [601]
+                io.output(memory.0[601]);
+                // 610: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(610))?;
+                // 614: jf #0, #617
+                if (0) == 0 { (617) } else { 617 }
+            }
+            617 => {
+                memory.check(617, 630)?;
+                // 617: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(617))?;
+                // 621: arb #-8
+                rb = i64::checked_add(rb, (-8)).ok_or(Error::Overflow(621))?;
+                // 623: eq #33, rb+4, [63]
+                memory.0[63] = ((33) == memory.read(623, rb + (4))?) as i64;
+                // 627: jt [63], #635
+                if memory.0[63] != 0 { (635) } else { 630 }
+            }
+            630 => {
+                memory.check(630, 635)?;
+                // 630: out [623]
+                io.output(memory.0[623]);
+                // 632: jt #1, #639
+                if (1) != 0 { (639) } else { 635 }
+            }
+            635 => {
+                memory.check(635, 639)?;
+                // 635: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(635))?;
+                639
+            }
+            639 => {
+                memory.check(639, 656)?;
+                // 639: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(639))?;
+                // 643: arb #10
+                rb = i64::checked_add(rb, (10)).ok_or(Error::Overflow(643))?;
+                // 645: mul rb-1, #1, [63]
+                memory.0[63] = i64::checked_mul(memory.read(645, rb + (-1))?, (1)).ok_or(Error::Overflow(645))?;
+                // 649: eq [63], #26, [63]
+                memory.0[63] = (memory.0[63] == (26)) as i64;
+                // 653: jt [63], #665
+                if memory.0[63] != 0 { (665) } else { 656 }
+            }
+            656 => {
+                memory.check(656, 665)?;
+                // 656: out [645]
+                io.output(memory.0[645]);
+                // 658: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(658))?;
+                // 662: jt #1, #665
+                if (1) != 0 { (665) } else { 665 }
+            }
+            665 => {
+                memory.check(665, 678)?;
+                // 665: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(665))?;
+                // 669: arb #-9
+                rb = i64::checked_add(rb, (-9)).ok_or(Error::Overflow(669))?;
+                // 671: lt #30, rb+1, [63]
+                memory.0[63] = ((30) < memory.read(671, rb + (1))?) as i64;
+                // 675: jt [63], #685
+                if memory.0[63] != 0 { (685) } else { 678 }
+            }
+            678 => {
+                memory.check(678, 685)?;
+                // 678: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(678))?;
+                // 682: jt #1, #687
+                if (1) != 0 { (687) } else { 685 }
+            }
+            685 => {
+                memory.check(685, 687)?;
+                // 685: out [671]
+                io.output(memory.0[671]);
+                687
+            }
+            687 => {
+                memory.check(687, 696)?;
+                // 687: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(687))?;
+                // 691: arb #25
+                rb = i64::checked_add(rb, (25)).ok_or(Error::Overflow(691))?;
+                // 693: jt rb-4, #703
+                if memory.read(693, rb + (-4))? != 0 { (703) } else { 696 }
+            }
+            696 => {
+                memory.check(696, 703)?;
+                // 696: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(696))?;
+                // 700: jt #1, #705
+                if (1) != 0 { (705) } else { 703 }
+            }
+            703 => {
+                memory.check(703, 705)?;
+                // 703: out [693]
+                io.output(memory.0[693]);
+                705
+            }
+            705 => {
+                memory.check(705, 722)?;
+                // 705: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(705))?;
+                // 709: arb #-19
+                rb = i64::checked_add(rb, (-19)).ok_or(Error::Overflow(709))?;
+                // 711: add #0, rb-5, [63]
+                memory.0[63] = i64::checked_add((0), memory.read(711, rb + (-5))?).ok_or(Error::Overflow(711))?;
+                // 715: eq [63], #26, [63]
+                memory.0[63] = (memory.0[63] == (26)) as i64;
+                // 719: jt [63], #725
+                if memory.0[63] != 0 { (725) } else { 722 }
+            }
+            722 => {
+                memory.check(722, 725)?;
+                // 722: jt #1, #731
+                if (1) != 0 { (731) } else { 725 }
+            }
+            725 => {
+                memory.check(725, 731)?;
+                // 725: out [711]
+                io.output(memory.0[711]);
+                // 727: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(727))?;
+                731
+            }
+            731 => {
+                memory.check(731, 744)?;
+                // 731: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(731))?;
+                // 735: arb #6
+                rb = i64::checked_add(rb, (6)).ok_or(Error::Overflow(735))?;
+                // 737: lt rb-2, #26, [63]
+                memory.0[63] = (memory.read(737, rb + (-2))? < (26)) as i64;
+                // 741: jt [63], #749
+                if memory.0[63] != 0 { (749) } else { 744 }
+            }
+            744 => {
+                memory.check(744, 749)?;
+                // 744: out [737]
+                io.output(memory.0[737]);
+                // 746: jt #1, #753
+                if (1) != 0 { (753) } else { 749 }
+            }
+            749 => {
+                memory.check(749, 753)?;
+                // 749: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(749))?;
+                753
+            }
+            753 => {
+                memory.check(753, 766)?;
+                // 753: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(753))?;
+                // 757: arb #-10
+                rb = i64::checked_add(rb, (-10)).ok_or(Error::Overflow(757))?;
+                // 759: eq #45, #46, rb+9
+                memory.write(759, rb + (9), ((45) == (46)) as i64)?;
+                // 763: jt [1010], #769
+                if memory.read(763, 1010)? != 0 { (769) } else { 766 }
+            }
+            766 => {
+                memory.check(766, 769)?;
+                // 766: jt #1, #775
+                if (1) != 0 { (775) } else { 769 }
+            }
+            769 => {
+                memory.check(769, 775)?;
+                // 769: out [759]
+                io.output(memory.0[759]);
+                // 771: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(771))?;
+                775
+            }
+            775 => {
+                memory.check(775, 792)?;
+                // 775: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(775))?;
+                // 779: arb #-10
+                rb = i64::checked_add(rb, (-10)).ok_or(Error::Overflow(779))?;
+                // 781: add rb+10, #0, [63]
+                memory.0[63] = i64::checked_add(memory.read(781, rb + (10))?, (0)).ok_or(Error::Overflow(781))?;
+                // 785: eq [63], #30, [63]
+                memory.0[63] = (memory.0[63] == (30)) as i64;
+                // 789: jt [63], #801
+                if memory.0[63] != 0 { (801) } else { 792 }
+            }
+            792 => {
+                memory.check(792, 801)?;
+                // 792: out [781]
+                io.output(memory.0[781]);
+                // 794: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(794))?;
+                // 798: jf #0, #801
+                if (0) == 0 { (801) } else { 801 }
+            }
+            801 => {
+                memory.check(801, 814)?;
+                // 801: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(801))?;
+                // 805: arb #21
+                rb = i64::checked_add(rb, (21)).ok_or(Error::Overflow(805))?;
+                // 807: eq #46, #46, rb+3
+                memory.write(807, rb + (3), ((46) == (46)) as i64)?;
+                // 811: jt [1015], #819
+                if memory.read(811, 1015)? != 0 { (819) } else { 814 }
+            }
+            814 => {
+                memory.check(814, 819)?;
+                // 814: out [807]
+                io.output(memory.0[807]);
+                // 816: jf #0, #823
+                if (0) == 0 { (823) } else { 819 }
+            }
+            819 => {
+                memory.check(819, 823)?;
+                // 819: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(819))?;
+                823
+            }
+            823 => {
+                memory.check(823, 840)?;
+                // 823: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(823))?;
+                // 827: arb #-4
+                rb = i64::checked_add(rb, (-4)).ok_or(Error::Overflow(827))?;
+                // 829: mul #1, rb-3, [63]
+                memory.0[63] = i64::checked_mul((1), memory.read(829, rb + (-3))?).ok_or(Error::Overflow(829))?;
+                // 833: eq [63], #31, [63]
+                memory.0[63] = (memory.0[63] == (31)) as i64;
+                // 837: jt [63], #849
+                if memory.0[63] != 0 { (849) } else { 840 }
+            }
+            840 => {
+                memory.check(840, 849)?;
+                // 840: out [829]
+                io.output(memory.0[829]);
+                // 842: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(842))?;
+                // 846: jf #0, #849
+                if (0) == 0 { (849) } else { 849 }
+            }
+            849 => {
+                memory.check(849, 866)?;
+                // 849: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(849))?;
+                // 853: arb #-5
+                rb = i64::checked_add(rb, (-5)).ok_or(Error::Overflow(853))?;
+                // 855: add #0, rb+1, [63]
+                memory.0[63] = i64::checked_add((0), memory.read(855, rb + (1))?).ok_or(Error::Overflow(855))?;
+                // 859: eq [63], #22, [63]
+                memory.0[63] = (memory.0[63] == (22)) as i64;
+                // 863: jt [63], #875
+                if memory.0[63] != 0 { (875) } else { 866 }
+            }
+            866 => {
+                memory.check(866, 875)?;
+                // 866: out [855]
+                io.output(memory.0[855]);
+                // 868: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(868))?;
+                // 872: jt #1, #875
+                if (1) != 0 { (875) } else { 875 }
+            }
+            875 => {
+                memory.check(875, 892)?;
+                // 875: mul [64], #2, [64]
+                memory.0[64] = i64::checked_mul(memory.0[64], (2)).ok_or(Error::Overflow(875))?;
+                // 879: arb #17
+                rb = i64::checked_add(rb, (17)).ok_or(Error::Overflow(879))?;
+                // 881: add #47, #0, rb-3
+                memory.write(881, rb + (-3), i64::checked_add((47), (0)).ok_or(Error::Overflow(881))?)?;
+                // 885: eq [1017], #47, [63]
+                memory.0[63] = (memory.read(885, 1017)? == (47)) as i64;
+                // 889: jt [63], #897
+                if memory.0[63] != 0 { (897) } else { 892 }
+            }
+            892 => {
+                memory.check(892, 897)?;
+                // 892: out [881]
+                io.output(memory.0[881]);
+                // 894: jt #1, #901
+                if (1) != 0 { (901) } else { 897 }
+            }
+            897 => {
+                memory.check(897, 901)?;
+                // 897: add [64], #1, [64]
+                memory.0[64] = i64::checked_add(memory.0[64], (1)).ok_or(Error::Overflow(897))?;
+                901
+            }
+            901 => {
+                memory.check(901, 904)?;
+                // 901: out [64]
+                io.output(memory.0[64]);
+                // 903: hlt
+                return Ok(());
+            }
+            904 => {
+                // 904: add #0, #27, rb+1
+                memory.write(904, rb + (1), i64::checked_add((0), (27)).ok_or(Error::Overflow(904))?)?;
+                // 908: mul #1, #915, rb+0
+                memory.write(908, rb + (0), i64::checked_mul((1), (915)).ok_or(Error::Overflow(908))?)?;
+                // 912: jt #1, #922
+                if (1) != 0 { (922) } else { 915 }
+            }
+            915 => {
+                memory.check(915, 922)?;
+                // 915: add rb+1, #38480, rb+1
+                memory.write(915, rb + (1), i64::checked_add(memory.read(915, rb + (1))?, (38480)).ok_or(Error::Overflow(915))?)?;
+                // 919: out rb+1
+                io.output(memory.read(919, rb + (1))?);
+                // 921: hlt
+                return Ok(());
+            }
+            922 => {
+                // 922: arb #3
+                rb = i64::checked_add(rb, (3)).ok_or(Error::Overflow(922))?;
+                // 924: lt rb-2, #3, [63]
+                memory.0[63] = (memory.read(924, rb + (-2))? < (3)) as i64;
+                // 928: jt [63], #964
+                if memory.0[63] != 0 { (964) } else { 931 }
+            }
+            931 => {
+                // 931: add rb-2, #-1, rb+1
+                memory.write(931, rb + (1), i64::checked_add(memory.read(931, rb + (-2))?, (-1)).ok_or(Error::Overflow(931))?)?;
+                // 935: add #0, #942, rb+0
+                memory.write(935, rb + (0), i64::checked_add((0), (942)).ok_or(Error::Overflow(935))?)?;
+                // 939: jf #0, #922
+                if (0) == 0 { (922) } else { 942 }
+            }
+            942 => {
+                memory.check(942, 957)?;
+                // 942: mul rb+1, #1, rb-1
+                memory.write(942, rb + (-1), i64::checked_mul(memory.read(942, rb + (1))?, (1)).ok_or(Error::Overflow(942))?)?;
+                // 946: add rb-2, #-3, rb+1
+                memory.write(946, rb + (1), i64::checked_add(memory.read(946, rb + (-2))?, (-3)).ok_or(Error::Overflow(946))?)?;
+                // 950: add #957, #0, rb+0
+                memory.write(950, rb + (0), i64::checked_add((957), (0)).ok_or(Error::Overflow(950))?)?;
+                // 954: jt #1, #922
+                if (1) != 0 { (922) } else { 957 }
+            }
+            957 => {
+                memory.check(957, 964)?;
+                // 957: add rb+1, rb-1, rb-2
+                memory.write(957, rb + (-2), i64::checked_add(memory.read(957, rb + (1))?, memory.read(957, rb + (-1))?).ok_or(Error::Overflow(957))?)?;
+                // 961: jf #0, #968
+                if (0) == 0 { (968) } else { 964 }
+            }
+            964 => {
+                // 964: add #0, rb-2, rb-2
+                memory.write(964, rb + (-2), i64::checked_add((0), memory.read(964, rb + (-2))?).ok_or(Error::Overflow(964))?)?;
+                968
+            }
+            968 => {
+                // 968: arb #-3
+                rb = i64::checked_add(rb, (-3)).ok_or(Error::Overflow(968))?;
+                // 970: jt #1, rb+0
+                if (1) != 0 { memory.read(970, rb + (0))? } else { 973 }
+            }
+            other => return Err(Error::BadJump(other)),
+        };
+    }
+}
@@ -0,0 +1,99 @@
+// Generated by intcode-compile from a program of 14 values. Do not edit.
+#![cfg_attr(rustfmt, rustfmt::skip)]
+#![allow(clippy::all, dead_code, unused_mut, unused_parens, unused_variables)]
+
+pub trait Io {
+    fn input(&mut self) -> Option<i64>;
+    fn output(&mut self, value: i64);
+}
+
+// Each error carries the address of the instruction that failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    InputClosed(usize),
+    NegativeAddress(usize, i64),
+    // The program wrote to one of its reachable instructions.
+    CodeWrite(usize, usize),
+    // The block at the address was about to run after the program had written over it.
+    ModifiedCode(usize),
+    Overflow(usize),
+    // Execution continued somewhere that isn't a compiled block.
+    BadJump(i64),
+}
+
+// Also whether the program has written to any unreached code.
+struct Memory(Vec<i64>, bool);
+
+impl Memory {
+    #[inline]
+    fn read(&self, at: usize, address: i64) -> Result<i64, Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        Ok(self.0.get(address as usize).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    fn write(&mut self, at: usize, address: i64, value: i64) -> Result<(), Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        let address = address as usize;
+        if is_code(address) {
+            return Err(Error::CodeWrite(at, address));
+        }
+        if is_unreached(address) {
+            self.1 = true;
+        }
+        if address >= self.0.len() {
+            self.0.resize(address + 1, 0);
+        }
+        self.0[address] = value;
+        Ok(())
+    }
+
+    #[inline]
+    fn check(&self, start: usize, end: usize) -> Result<(), Error> {
+        if self.1 && self.0[start..end] != PROGRAM[start..end] {
+            return Err(Error::ModifiedCode(start));
+        }
+        Ok(())
+    }
+}
+
+fn is_code(address: usize) -> bool {
+    matches!(address, 0..=8)
+}
+
+fn is_unreached(address: usize) -> bool {
+    matches!(address, 9..=12)
+}
+
+static PROGRAM: [i64; 14] = [3, 13, 1002, 13, 4611686018427387904, 9, 4, 9, 99, 1, 0, 0, 0, 0];
+
+pub fn run<T: Io>(io: &mut T) -> Result<(), Error> {
+    let mut memory = Memory(PROGRAM.to_vec(), false);
+    let mut rb: i64 = 0;
+    let mut block: i64 = 0;
+    loop {
+        block = match block {
+            0 => {
+                // 0: in [13]
+                memory.0[13] = io.input().ok_or(Error::InputClosed(0))?;
+                // 2: mul [13], #4611686018427387904, [9]
+                memory.write(2, 9, i64::checked_mul(memory.0[13], (4611686018427387904)).ok_or(Error::Overflow(2))?)?;
+                // 6: out [9]
+                io.output(memory.0[9]);
+                // 8: hlt
+                return Ok(());
+            }
+            9 => {
+                memory.check(9, 13)?;
+                // 9: add [0], [0], [0]
+                memory.write(9, 0, i64::checked_add(memory.0[0], memory.0[0]).ok_or(Error::Overflow(9))?)?;
+                13
+            }
+            other => return Err(Error::BadJump(other)),
+        };
+    }
+}
@@ -21,8 +21,8 @@ fn read_file(path: &str) -> std::io::Result<Vec<f64>> {
 }
 
 fn main() {
-    let numbers =
-        read_file("/home/tim/projects/AoC19/resources/day1input").expect("unable to load numbers");
+    let numbers = read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day1input"))
+        .expect("unable to load numbers");
     let fuel: f64 = numbers.iter().map(|n| calculate_fuel(*n)).sum();
     println!("{:?}", fuel);
 }
@@ -129,8 +129,8 @@ impl Station {
 }
 
 fn main() {
-    let input =
-        read_input("/home/tim/projects/AoC19/resources/day10input").expect("failed to load input");
+    let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day10input"))
+        .expect("failed to load input");
     println!("{:?}", find_optimal_point(&input));
     let station = Station::position_at_optimal_place(input);
     let destroyed = station.solve();
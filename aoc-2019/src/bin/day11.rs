@@ -1,7 +1,11 @@
+use lib::int_code::executor::{channel, run, Executor};
+use lib::int_code::recording::Recorder;
 use lib::int_code::{machine::Machine, read_file};
 use std::collections::HashMap;
-use std::sync::mpsc;
-use std::thread;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+static USAGE: &str = "usage: day11 [--record <file>]";
 
 #[derive(Copy, Clone, Debug)]
 enum Colour {
@@ -98,60 +102,87 @@ impl Canvas {
     }
 }
 
-fn solve(program: Vec<i64>, starting_colour: Colour) -> Canvas {
-    let mut canvas = Canvas::new();
-    let mut position = Coordinate { x: 0, y: 0 };
-    let mut heading = Heading::North;
-    canvas.paint(position.clone(), starting_colour);
-
-    let (input_tx, input_rx) = mpsc::channel();
-    let (output_tx, output_rx) = mpsc::channel();
-    let mut machine = Machine::new(program, input_rx, output_tx);
-
-    thread::spawn(move || {
-        machine.execute().expect("failed to excute machine");
+fn solve(
+    program: Vec<i64>,
+    starting_colour: Colour,
+    recorder: Option<Arc<Mutex<Recorder>>>,
+) -> Canvas {
+    let (input_tx, input_rx) = channel();
+    let (output_tx, mut output_rx) = channel();
+    let mut executor = Executor::new();
+    let machine = Machine::new(program, input_rx, output_tx);
+    let machine = match recorder {
+        Some(recorder) => machine.with_tracer(recorder),
+        None => machine,
+    };
+    let machine = executor.spawn(run(machine));
+
+    let canvas = executor.spawn(async move {
+        let mut canvas = Canvas::new();
+        let mut position = Coordinate { x: 0, y: 0 };
+        let mut heading = Heading::North;
+        canvas.paint(position, starting_colour);
+        loop {
+            let input = match canvas.get_colour(&position).unwrap_or(&Colour::Black) {
+                Colour::Black => 0,
+                Colour::White => 1,
+            };
+            input_tx.send(input);
+            // the robot stops when the machine halts and closes its output.
+            let colour_to_paint = match output_rx.recv().await {
+                Some(0) => Colour::Black,
+                Some(1) => Colour::White,
+                Some(_) => panic!("unknown response for colour to paint"),
+                None => break,
+            };
+            canvas.paint(position, colour_to_paint);
+            let direction = match output_rx.recv().await {
+                Some(0) => Direction::Left,
+                Some(1) => Direction::Right,
+                Some(_) => panic!("unknown response for direction"),
+                None => panic!("failed to get direction"),
+            };
+            heading = heading.turn(direction);
+            position.x += heading.change_x();
+            position.y += heading.change_y();
+        }
+        canvas
     });
 
-    loop {
-        let input = match canvas.get_colour(&position).unwrap_or(&Colour::Black) {
-            Colour::Black => 0,
-            Colour::White => 1,
-        };
-        if input_tx.send(input).is_err() {
-            break;
-        }
-        let colour_to_paint = match output_rx.recv() {
-            Ok(0) => Colour::Black,
-            Ok(1) => Colour::White,
-            Ok(_) => panic!("unknown response for colour to paint"),
-            Err(e) => {
-                eprintln!("failed to get colour {}", e);
-                break;
-            }
-        };
-        canvas.paint(position.clone(), colour_to_paint);
-        let direction = match output_rx.recv() {
-            Ok(0) => Direction::Left,
-            Ok(1) => Direction::Right,
-            Ok(_) => panic!("unknown response for direction"),
-            Err(e) => {
-                eprintln!("failed to get direction {}", e);
-                break;
-            }
-        };
-        heading = heading.turn(direction);
-        position.x += heading.change_x();
-        position.y += heading.change_y();
-    }
-    canvas
+    executor.run().expect("robot stalled");
+    machine
+        .take()
+        .expect("machine didn't finish")
+        .expect("failed to execute machine");
+    canvas.take().expect("robot didn't finish")
 }
 
 fn main() {
-    let input =
-        read_file("/home/tim/projects/AoC19/resources/day11input").expect("failed to read input");
+    let mut record = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record = Some(args.next().expect(USAGE)),
+            _ => panic!("{}", USAGE),
+        }
+    }
+
+    let input = read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day11input"))
+        .expect("failed to read input");
     println!(
         "part1 outputs = {:?}",
-        solve(input.clone(), Colour::Black).canvas.len()
+        solve(input.clone(), Colour::Black, None).canvas.len()
     );
-    solve(input, Colour::White).print();
+    // only the run that paints the registration is recorded.
+    let recorder = record
+        .as_ref()
+        .map(|_| Arc::new(Mutex::new(Recorder::new())));
+    solve(input, Colour::White, recorder.clone()).print();
+    if let (Some(path), Some(recorder)) = (record, recorder) {
+        let recorder = recorder.lock().expect("tracer lock poisoned");
+        recorder
+            .recording()
+            .save(&path)
+            .expect("failed to save recording");
+    }
 }
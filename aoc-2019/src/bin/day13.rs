@@ -1,16 +1,179 @@
-use lib::int_code::{machine::Machine, monitor::Monitor, read_file};
-use std::sync::mpsc;
+use lib::int_code::machine::{ExecutionState, Machine};
+use lib::int_code::monitor::{Backend, DisplayObject, Headless, Monitor, Screen, Terminal};
+use lib::int_code::read_file;
+use lib::int_code::recording::Recorder;
+use std::collections::VecDeque;
+use std::env;
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
+use termion::event::Key;
+use termion::input::TermRead;
+
+static USAGE: &str =
+    "usage: day13 [--manual | --auto] [--free-play] [--headless] [--record <file>]";
+// How long each frame is shown for when someone is watching.
+static FRAME: Duration = Duration::from_millis(100);
+static AUTOPILOT_FRAME: Duration = Duration::from_millis(10);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Watch,
+    Manual,
+    Autopilot,
+}
+
+struct Game {
+    program: Vec<i64>,
+    mode: Mode,
+    recorder: Option<Arc<Mutex<Recorder>>>,
+}
+
+// Moves the paddle towards the ball.
+fn autopilot(screen: &Screen) -> i64 {
+    match (
+        screen.find(DisplayObject::Ball),
+        screen.find(DisplayObject::HorizPaddle),
+    ) {
+        (Some(ball), Some(paddle)) => (ball.x as i64 - paddle.x as i64).signum(),
+        _ => 0,
+    }
+}
+
+// Runs the game, asking `joystick` for a position whenever the game reads one. The game
+// stops early if `joystick` returns None.
+fn play<B, J>(game: &Game, backend: B, mut joystick: J) -> Screen
+where
+    B: Backend,
+    J: FnMut(&Screen) -> Option<i64>,
+{
+    let machine = Machine::new(game.program.clone(), VecDeque::new(), Monitor::new(backend));
+    let mut machine = match &game.recorder {
+        Some(recorder) => machine.with_tracer(recorder.clone()),
+        None => machine,
+    };
+    loop {
+        match machine.run_until_io().expect("failed executing machine") {
+            ExecutionState::NeedsInput => match joystick(machine.output().screen()) {
+                Some(position) => machine.push_input(position),
+                None => break,
+            },
+            ExecutionState::Halted => break,
+            _ => {}
+        }
+    }
+    let mut monitor = machine.into_output();
+    monitor.finish().expect("failed to draw");
+    monitor.screen().clone()
+}
+
+fn run<B: Backend>(game: &Game, backend: B, watched: bool) -> Screen {
+    match game.mode {
+        Mode::Watch => play(game, backend, |_| Some(0)),
+        Mode::Autopilot => play(game, backend, |screen| {
+            if watched {
+                thread::sleep(AUTOPILOT_FRAME);
+            }
+            Some(autopilot(screen))
+        }),
+        Mode::Manual => {
+            let mut keys = termion::async_stdin().keys();
+            play(game, backend, move |_| {
+                thread::sleep(FRAME);
+                let mut position = 0;
+                // only keys pressed during the last frame count.
+                for key in keys.by_ref() {
+                    match key {
+                        Ok(Key::Left) | Ok(Key::Char('a')) => position = -1,
+                        Ok(Key::Right) | Ok(Key::Char('d')) => position = 1,
+                        Ok(Key::Char('q')) | Ok(Key::Ctrl('c')) => return None,
+                        _ => {}
+                    }
+                }
+                Some(position)
+            })
+        }
+    }
+}
 
 fn main() {
-    let program =
-        read_file("/home/tim/projects/AoC19/resources/day13input").expect("failed to read input");
-    let (input_tx, input_rx) = mpsc::channel();
-    let (output_tx, output_rx) = mpsc::channel();
-    let mut machine = Machine::new(program, input_rx, output_tx);
-    let monitor = Monitor::new(output_rx);
-    thread::spawn(move || {
-        machine.execute().expect("failed excuting machine");
-    });
-    monitor.start();
+    let mut mode = Mode::Watch;
+    let mut free_play = false;
+    let mut headless = false;
+    let mut record = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record = Some(args.next().expect(USAGE)),
+            "--manual" => mode = Mode::Manual,
+            "--auto" => mode = Mode::Autopilot,
+            "--free-play" => free_play = true,
+            "--headless" => headless = true,
+            _ => panic!("{}", USAGE),
+        }
+    }
+    if mode == Mode::Manual && headless {
+        panic!("manual play needs a terminal");
+    }
+
+    let mut program = read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day13input"))
+        .expect("failed to read input");
+    // two quarters.
+    let patches = if free_play { vec![(0, 2)] } else { vec![] };
+    for (address, value) in &patches {
+        program[*address] = *value;
+    }
+    let game = Game {
+        program,
+        mode,
+        recorder: record
+            .as_ref()
+            .map(|_| Arc::new(Mutex::new(Recorder::new().with_patches(&patches)))),
+    };
+    let screen = if headless {
+        run(&game, Headless, false)
+    } else {
+        let terminal = Terminal::new().expect("failed to get raw terminal");
+        run(&game, terminal, true)
+    };
+    if let (Some(path), Some(recorder)) = (record, game.recorder) {
+        let recorder = recorder.lock().expect("tracer lock poisoned");
+        recorder
+            .recording()
+            .save(&path)
+            .expect("failed to save recording");
+    }
+    println!("blocks left = {}", screen.count(DisplayObject::Block));
+    if let Some(score) = screen.score() {
+        println!("final score = {}", score);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(mode: Mode) -> Game {
+        Game {
+            program: read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day13input"))
+                .expect("failed to read input"),
+            mode,
+            recorder: None,
+        }
+    }
+
+    #[test]
+    fn part1() {
+        let screen = run(&game(Mode::Watch), Headless, false);
+        assert_eq!(screen.count(DisplayObject::Block), 398);
+    }
+
+    #[test]
+    fn part2() {
+        let mut game = game(Mode::Autopilot);
+        game.program[0] = 2;
+        let screen = run(&game, Headless, false);
+        assert_eq!(screen.count(DisplayObject::Block), 0);
+        assert_eq!(screen.score(), Some(19_447));
+    }
 }
@@ -217,8 +217,8 @@ fn solve(path1: Vec<Point>, path2: Vec<Point>) -> i32 {
 }
 
 fn main() {
-    let paths =
-        read_file("/home/tim/projects/AoC19/resources/day3input").expect("failed to load input");
+    let paths = read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day3input"))
+        .expect("failed to load input");
     let path1: Vec<Point> = Path::new(paths.0).collect();
     let path2: Vec<Point> = Path::new(paths.1).collect();
     println!("{}", solve(path1, path2));
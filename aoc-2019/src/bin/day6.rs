@@ -153,8 +153,8 @@ impl System {
 }
 
 fn main() {
-    let orbit_relations =
-        read_input("/home/tim/projects/AoC19/resources/day6input").expect("unable to load input");
+    let orbit_relations = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day6input"))
+        .expect("unable to load input");
     let system = System::new(orbit_relations, "COM".to_owned());
     println!("answer p1 = {}", system.solve_part1());
     println!("answer p2 = {}", system.solve_part2());
@@ -139,8 +139,8 @@ impl ImageDecoder {
 }
 
 fn main() {
-    let raw_img =
-        read_file("/home/tim/projects/AoC19/resources/day8input").expect("unable to load input");
+    let raw_img = read_file(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/day8input"))
+        .expect("unable to load input");
     let image_decoder = ImageDecoder::new(raw_img, 25, 6);
     println!("{:?}", image_decoder.part_1());
     println!("{:?}", image_decoder.part_2());
@@ -0,0 +1,267 @@
+use lib::int_code::ascii::AsciiMachine;
+use lib::int_code::read_file;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Stdin, Stdout, Write};
+use termion::event::Key;
+use termion::input::{Keys, TermRead};
+use termion::raw::{IntoRawMode, RawTerminal};
+
+static USAGE: &str = "usage: intcode-ascii [--script <file>]... [--record <file>] <program file>";
+static PROMPT: &str = "> ";
+
+enum Edit {
+    Continue,
+    Done(String),
+    Exit,
+}
+
+// A single line editor with history, browsed with the up and down arrows.
+struct LineEditor {
+    history: Vec<String>,
+    line: Vec<char>,
+    cursor: usize,
+    // The history entry being shown and the line that was being typed before browsing.
+    browsing: Option<(usize, Vec<char>)>,
+}
+
+impl LineEditor {
+    fn new() -> Self {
+        LineEditor {
+            history: Vec::new(),
+            line: Vec::new(),
+            cursor: 0,
+            browsing: None,
+        }
+    }
+
+    fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    fn _show(&mut self, line: Vec<char>) {
+        self.cursor = line.len();
+        self.line = line;
+    }
+
+    fn _history(&mut self, up: bool) {
+        let (index, typed) = match self.browsing.take() {
+            Some(browsing) => browsing,
+            None => (self.history.len(), self.line.clone()),
+        };
+        let index = match up {
+            true if index > 0 => index - 1,
+            true => index,
+            false => index + 1,
+        };
+        if index < self.history.len() {
+            self._show(self.history[index].chars().collect());
+            self.browsing = Some((index, typed));
+        } else {
+            self._show(typed);
+        }
+    }
+
+    fn key(&mut self, key: Key) -> Edit {
+        match key {
+            Key::Char('\n') => {
+                let line = self.line();
+                if !line.is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                self.browsing = None;
+                self._show(Vec::new());
+                return Edit::Done(line);
+            }
+            Key::Ctrl('c') => return Edit::Exit,
+            Key::Ctrl('d') if self.line.is_empty() => return Edit::Exit,
+            Key::Char(c) => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            Key::Delete | Key::Ctrl('d') if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            Key::Left if self.cursor > 0 => self.cursor -= 1,
+            Key::Right if self.cursor < self.line.len() => self.cursor += 1,
+            Key::Home | Key::Ctrl('a') => self.cursor = 0,
+            Key::End | Key::Ctrl('e') => self.cursor = self.line.len(),
+            Key::Up => self._history(true),
+            Key::Down => self._history(false),
+            _ => {}
+        }
+        Edit::Continue
+    }
+}
+
+// Reads lines from the terminal in raw mode so that keys can be handled as they are typed,
+// or plain lines when stdin isn't a terminal.
+struct Console {
+    editor: LineEditor,
+    terminal: Option<(Keys<Stdin>, RawTerminal<Stdout>)>,
+}
+
+impl Console {
+    fn new() -> io::Result<Self> {
+        let terminal = if termion::is_tty(&io::stdin()) && termion::is_tty(&io::stdout()) {
+            let stdout = io::stdout().into_raw_mode()?;
+            stdout.suspend_raw_mode()?;
+            Some((io::stdin().keys(), stdout))
+        } else {
+            None
+        };
+        Ok(Console {
+            editor: LineEditor::new(),
+            terminal,
+        })
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let (keys, stdout) = match &mut self.terminal {
+            Some(terminal) => terminal,
+            None => {
+                let mut line = String::new();
+                return match BufRead::read_line(&mut io::stdin().lock(), &mut line)? {
+                    0 => Ok(None),
+                    _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+                };
+            }
+        };
+        stdout.activate_raw_mode()?;
+        let mut result = Ok(None);
+        loop {
+            let line = self.editor.line();
+            write!(
+                stdout,
+                "\r{}{}{}",
+                termion::clear::CurrentLine,
+                PROMPT,
+                line
+            )?;
+            let behind = self.editor.line.len() - self.editor.cursor;
+            if behind > 0 {
+                write!(stdout, "{}", termion::cursor::Left(behind as u16))?;
+            }
+            stdout.flush()?;
+            let key = match keys.next() {
+                Some(key) => key?,
+                None => break,
+            };
+            match self.editor.key(key) {
+                Edit::Continue => {}
+                Edit::Done(line) => {
+                    result = Ok(Some(line));
+                    break;
+                }
+                Edit::Exit => break,
+            }
+        }
+        write!(stdout, "\r\n")?;
+        stdout.suspend_raw_mode()?;
+        result
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let mut scripted = VecDeque::new();
+    let mut record = None;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--script" => {
+                let script = fs::read_to_string(args.next().expect(USAGE))?;
+                scripted.extend(script.lines().map(String::from));
+            }
+            "--record" => record = Some(File::create(args.next().expect(USAGE))?),
+            _ => path = Some(arg),
+        }
+    }
+    let program = read_file(&path.expect(USAGE))?;
+
+    let mut machine = AsciiMachine::new(program);
+    let mut console = Console::new()?;
+    loop {
+        let response = match machine.run() {
+            Ok(response) => response,
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        };
+        print!("{}", response.text);
+        for answer in response.answers {
+            println!("answer: {}", answer);
+        }
+        if response.halted {
+            return Ok(());
+        }
+        // scripted lines are replayed before anything is read from the terminal.
+        let line = match scripted.pop_front() {
+            Some(line) => {
+                println!("{}{}", PROMPT, line);
+                line
+            }
+            None => match console.read_line()? {
+                Some(line) => line,
+                None => return Ok(()),
+            },
+        };
+        if let Some(file) = &mut record {
+            writeln!(file, "{}", line)?;
+        }
+        machine.send_line(&line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_line(editor: &mut LineEditor, keys: &[Key]) -> Option<String> {
+        let mut line = None;
+        for key in keys.iter().chain(&[Key::Char('\n')]) {
+            if let Edit::Done(done) = editor.key(*key) {
+                line = Some(done);
+            }
+        }
+        line
+    }
+
+    #[test]
+    fn editing() {
+        let mut editor = LineEditor::new();
+        let keys = [
+            Key::Char('n'),
+            Key::Char('r'),
+            Key::Char('t'),
+            Key::Char('x'),
+            Key::Backspace,
+            Key::Char('h'),
+            Key::Home,
+            Key::Right,
+            Key::Char('o'),
+            Key::End,
+        ];
+        assert_eq!(type_line(&mut editor, &keys), Some(String::from("north")));
+        assert!(matches!(editor.key(Key::Ctrl('d')), Edit::Exit));
+    }
+
+    #[test]
+    fn history() {
+        let mut editor = LineEditor::new();
+        type_line(&mut editor, &[Key::Char('a')]);
+        type_line(&mut editor, &[Key::Char('b')]);
+        type_line(&mut editor, &[Key::Char('b')]);
+        assert_eq!(editor.history, vec!["a", "b"]);
+        let keys = [Key::Char('c'), Key::Up, Key::Up, Key::Up];
+        assert_eq!(type_line(&mut editor, &keys), Some(String::from("a")));
+        let keys = [Key::Char('c'), Key::Up, Key::Down, Key::Char('d')];
+        assert_eq!(type_line(&mut editor, &keys), Some(String::from("cd")));
+    }
+}
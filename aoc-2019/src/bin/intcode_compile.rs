@@ -0,0 +1,18 @@
+use lib::int_code::{compiler, read_file};
+use std::env;
+use std::process;
+
+// Prints a program compiled to a Rust module.
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .expect("usage: intcode-compile <program file>");
+    let program = read_file(&path).expect("failed to read program");
+    match compiler::compile(&program) {
+        Ok(source) => print!("{}", source),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+}
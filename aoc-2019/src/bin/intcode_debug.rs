@@ -0,0 +1,325 @@
+use lib::int_code::instruction::{decode, mnemonic, Command};
+use lib::int_code::machine::{ExecutionState, Machine};
+use lib::int_code::read_file;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::env;
+use std::io::{self, BufRead, Write};
+
+static HELP: &str = "\
+s, step [n]          execute n instructions (default 1)
+c, continue          run until a breakpoint, watchpoint, input wait or halt
+b, break <addr>      stop when the program counter reaches addr
+bo, break-op <op>    stop before an opcode, given as a number or mnemonic
+w, watch <addr>      stop when the program writes to the cell at addr
+d, delete            remove all breakpoints and watchpoints
+i, input <v>...      queue input values
+x <addr> [n]         dump n cells (default 8) starting at addr
+r, regs              print the program counter and relative base
+l, list [n]          disassemble n instructions (default 5) from the program counter
+o, output            print every value output so far
+h, help              print this message
+q, quit              exit";
+
+enum Stop {
+    Breakpoint(usize),
+    Opcode(i64),
+    Watchpoint(usize, i64, i64),
+    NeedsInput,
+    Halted,
+    Error(String),
+}
+
+struct Debugger {
+    machine: Machine<VecDeque<i64>, Vec<i64>>,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: BTreeSet<i64>,
+    watchpoints: BTreeMap<usize, i64>,
+}
+
+impl Debugger {
+    fn new(program: Vec<i64>) -> Self {
+        Debugger {
+            machine: Machine::with_program(program),
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+        }
+    }
+
+    fn _command_at(&self, address: usize) -> Option<Command> {
+        let slice: Vec<i64> = (address..address + 4)
+            .map(|a| self.machine.read_address(a))
+            .collect();
+        decode(&slice).ok().map(|(command, _)| command)
+    }
+
+    // Executes one instruction, reporting why execution should stop, if it should.
+    fn _step(&mut self, out: &mut impl Write) -> io::Result<Option<Stop>> {
+        let stop = match self.machine.step() {
+            Ok(ExecutionState::Running) => None,
+            Ok(ExecutionState::Output(value)) => {
+                writeln!(out, "output: {}", value)?;
+                None
+            }
+            Ok(ExecutionState::NeedsInput) => Some(Stop::NeedsInput),
+            Ok(ExecutionState::Halted) => Some(Stop::Halted),
+            Err(e) => Some(Stop::Error(e.to_string())),
+        };
+        if stop.is_some() {
+            return Ok(stop);
+        }
+        if let Some((address, new)) = self.machine.read_last_write() {
+            if let Some(old) = self.watchpoints.get_mut(&address) {
+                let stop = Stop::Watchpoint(address, *old, new);
+                *old = new;
+                return Ok(Some(stop));
+            }
+        }
+        Ok(None)
+    }
+
+    fn _breakpoint(&self) -> Option<Stop> {
+        let pc = self.machine.read_program_counter();
+        if self.breakpoints.contains(&pc) {
+            return Some(Stop::Breakpoint(pc));
+        }
+        let opcode = self._command_at(pc)?.opcode();
+        if self.opcode_breakpoints.contains(&opcode) {
+            Some(Stop::Opcode(opcode))
+        } else {
+            None
+        }
+    }
+
+    fn _continue(&mut self, out: &mut impl Write) -> io::Result<Stop> {
+        // the first instruction always runs so we can continue from a breakpoint.
+        if let Some(stop) = self._step(out)? {
+            return Ok(stop);
+        }
+        loop {
+            if let Some(stop) = self._breakpoint() {
+                return Ok(stop);
+            }
+            if let Some(stop) = self._step(out)? {
+                return Ok(stop);
+            }
+        }
+    }
+
+    fn _report(&self, stop: Stop, out: &mut impl Write) -> io::Result<()> {
+        match stop {
+            Stop::Breakpoint(address) => writeln!(out, "breakpoint at {}", address)?,
+            Stop::Opcode(opcode) => writeln!(
+                out,
+                "breakpoint on {}",
+                mnemonic(opcode).unwrap_or("unknown opcode")
+            )?,
+            Stop::Watchpoint(address, old, new) => {
+                writeln!(out, "watchpoint [{}]: {} -> {}", address, old, new)?
+            }
+            Stop::NeedsInput => writeln!(out, "waiting for input, queue values with `input`")?,
+            Stop::Halted => writeln!(out, "halted")?,
+            Stop::Error(e) => writeln!(out, "{}", e)?,
+        }
+        self._list(1, out)
+    }
+
+    fn _list(&self, count: usize, out: &mut impl Write) -> io::Result<()> {
+        let mut address = self.machine.read_program_counter();
+        for _ in 0..count {
+            match self._command_at(address) {
+                Some(command) => {
+                    writeln!(out, "{:>5}: {}", address, command)?;
+                    address += command.length();
+                }
+                None => {
+                    writeln!(
+                        out,
+                        "{:>5}: data {}",
+                        address,
+                        self.machine.read_address(address)
+                    )?;
+                    address += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Runs one command line, returning false when the debugger should exit.
+    fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let numbers: Result<Vec<i64>, _> = words.iter().skip(1).map(|w| w.parse()).collect();
+        let numbers = match numbers {
+            Ok(numbers) => numbers,
+            Err(_) if matches!(words.first(), Some(&"bo") | Some(&"break-op")) => vec![],
+            Err(e) => {
+                writeln!(out, "bad argument: {}", e)?;
+                return Ok(true);
+            }
+        };
+        let arg = |i: usize| numbers.get(i).copied();
+        let address = |i: usize| arg(i).filter(|a| *a >= 0).map(|a| a as usize);
+        match words.first().copied() {
+            None => {}
+            Some("s") | Some("step") => {
+                for _ in 0..arg(0).unwrap_or(1) {
+                    if let Some(stop) = self._step(out)? {
+                        return self._report(stop, out).map(|_| true);
+                    }
+                }
+                self._list(1, out)?;
+            }
+            Some("c") | Some("continue") => {
+                let stop = self._continue(out)?;
+                self._report(stop, out)?;
+            }
+            Some("b") | Some("break") => match address(0) {
+                Some(address) => {
+                    self.breakpoints.insert(address);
+                }
+                None => writeln!(out, "usage: break <addr>")?,
+            },
+            Some("bo") | Some("break-op") => {
+                let opcode = words.get(1).and_then(|op| {
+                    op.parse::<i64>()
+                        .ok()
+                        .or_else(|| (1..=99).find(|code| mnemonic(*code) == Some(op)))
+                });
+                match opcode.filter(|code| mnemonic(*code).is_some()) {
+                    Some(opcode) => {
+                        self.opcode_breakpoints.insert(opcode);
+                    }
+                    None => writeln!(out, "usage: break-op <opcode or mnemonic>")?,
+                }
+            }
+            Some("w") | Some("watch") => match address(0) {
+                Some(address) => {
+                    let value = self.machine.read_address(address);
+                    self.watchpoints.insert(address, value);
+                }
+                None => writeln!(out, "usage: watch <addr>")?,
+            },
+            Some("d") | Some("delete") => {
+                self.breakpoints.clear();
+                self.opcode_breakpoints.clear();
+                self.watchpoints.clear();
+            }
+            Some("i") | Some("input") => {
+                for value in numbers {
+                    self.machine.push_input(value);
+                }
+            }
+            Some("x") => match address(0) {
+                Some(start) => {
+                    let count = address(1).unwrap_or(8);
+                    let values: Vec<String> = (start..start + count)
+                        .map(|a| self.machine.read_address(a).to_string())
+                        .collect();
+                    writeln!(out, "{:>5}: {}", start, values.join(" "))?;
+                }
+                None => writeln!(out, "usage: x <addr> [n]")?,
+            },
+            Some("r") | Some("regs") => writeln!(
+                out,
+                "pc = {} rb = {}",
+                self.machine.read_program_counter(),
+                self.machine.read_relative_base()
+            )?,
+            Some("l") | Some("list") => self._list(address(0).unwrap_or(5), out)?,
+            Some("o") | Some("output") => writeln!(out, "{:?}", self.machine.output())?,
+            Some("h") | Some("help") => writeln!(out, "{}", HELP)?,
+            Some("q") | Some("quit") => return Ok(false),
+            Some(other) => writeln!(out, "unknown command {}, try `help`", other)?,
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> io::Result<()> {
+    let path = env::args()
+        .nth(1)
+        .expect("usage: intcode-debug <program file>");
+    let program = read_file(&path)?;
+    let mut debugger = Debugger::new(program);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut last = String::new();
+    loop {
+        write!(stdout, "(icd) ")?;
+        stdout.flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        // an empty line repeats the previous command, like gdb.
+        if !line.trim().is_empty() {
+            last = line;
+        }
+        if !debugger.handle(&last, &mut stdout)? {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(debugger: &mut Debugger, line: &str) -> String {
+        let mut out = Vec::new();
+        debugger.handle(line, &mut out).expect("failed to write");
+        String::from_utf8(out).expect("output is utf8")
+    }
+
+    #[test]
+    fn breakpoints_and_input() {
+        let mut debugger = Debugger::new(vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "waiting for input, queue values with `input`\n    0: in [9]\n"
+        );
+        run(&mut debugger, "input 41");
+        run(&mut debugger, "break 6");
+        assert_eq!(
+            run(&mut debugger, "continue"),
+            "breakpoint at 6\n    6: out [9]\n"
+        );
+        assert_eq!(run(&mut debugger, "x 8 2"), "    8: 99 42\n");
+        assert_eq!(run(&mut debugger, "c"), "output: 42\nhalted\n    8: hlt\n");
+    }
+
+    #[test]
+    fn watchpoints_and_opcodes() {
+        let mut debugger = Debugger::new(vec![1101, 1, 2, 9, 109, 3, 1101, 0, 0, 0, 99]);
+        run(&mut debugger, "watch 9");
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "watchpoint [9]: 0 -> 3\n    4: arb #3\n"
+        );
+        run(&mut debugger, "bo add");
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "breakpoint on add\n    6: add #0, #0, [3]\n"
+        );
+        assert_eq!(run(&mut debugger, "regs"), "pc = 6 rb = 3\n");
+        run(&mut debugger, "delete");
+        assert_eq!(run(&mut debugger, "c"), "halted\n   10: hlt\n");
+    }
+
+    #[test]
+    fn watchpoints_on_writes() {
+        // writes 3 to [13] twice, then writes to [14].
+        let mut debugger = Debugger::new(vec![1101, 1, 2, 13, 1101, 2, 1, 13, 1101, 0, 0, 14, 99]);
+        run(&mut debugger, "watch 13");
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "watchpoint [13]: 0 -> 3\n    4: add #2, #1, [13]\n"
+        );
+        assert_eq!(
+            run(&mut debugger, "c"),
+            "watchpoint [13]: 3 -> 3\n    8: add #0, #0, [14]\n"
+        );
+        assert_eq!(run(&mut debugger, "c"), "halted\n   12: hlt\n");
+    }
+}
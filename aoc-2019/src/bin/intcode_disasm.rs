@@ -0,0 +1,17 @@
+use lib::int_code::{analysis, disassembler, read_file};
+use std::env;
+
+fn main() {
+    let usage = "usage: intcode-disasm [--dot] <program file>";
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let dot = args.first().map(|arg| arg == "--dot").unwrap_or(false);
+    if dot {
+        args.remove(0);
+    }
+    let program = read_file(args.first().expect(usage)).expect("failed to read program");
+    if dot {
+        print!("{}", analysis::analyse(&program).to_dot());
+    } else {
+        print!("{}", disassembler::listing(&program));
+    }
+}
@@ -0,0 +1,45 @@
+use lib::int_code::machine::{ExecutionState, Machine};
+use lib::int_code::read_file;
+use lib::int_code::trace::{Profiler, TraceWriter};
+use std::env;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+// Runs a program until it halts or runs out of input, then prints where it spent its time.
+fn main() {
+    let usage = "usage: intcode-profile [--trace] <program file> [input...]";
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let trace = args.first().map(|arg| arg == "--trace").unwrap_or(false);
+    if trace {
+        args.remove(0);
+    }
+    let program = read_file(args.first().expect(usage)).expect("failed to read program");
+    let inputs: Vec<i64> = args[1..]
+        .iter()
+        .map(|arg| arg.parse().expect(usage))
+        .collect();
+
+    let profiler = Arc::new(Mutex::new(Profiler::new()));
+    let machine = Machine::new(program, inputs, Vec::new());
+    let mut machine = if trace {
+        machine.with_tracer((profiler.clone(), TraceWriter::new(io::stdout())))
+    } else {
+        machine.with_tracer(profiler.clone())
+    };
+    loop {
+        match machine.run_until_io() {
+            Ok(ExecutionState::Halted) => break,
+            Ok(ExecutionState::NeedsInput) => {
+                println!("stopped waiting for input");
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                println!("{}", e);
+                break;
+            }
+        }
+    }
+    println!("outputs: {:?}", machine.output());
+    print!("{}", profiler.lock().expect("tracer lock poisoned"));
+}
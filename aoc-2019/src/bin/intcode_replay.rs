@@ -0,0 +1,28 @@
+use lib::int_code::read_file;
+use lib::int_code::recording::Recording;
+use std::env;
+use std::process;
+
+// Replays a recorded session against a program and checks the run matches the recording.
+fn main() {
+    let usage = "usage: intcode-replay <program file> <recording file>";
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("{}", usage);
+        process::exit(2);
+    }
+    let program = read_file(&args[0]).expect("failed to read program");
+    let recording = Recording::load(&args[1]).expect("failed to read recording");
+    println!(
+        "recorded {} inputs and {} outputs",
+        recording.inputs().len(),
+        recording.outputs().len()
+    );
+    match recording.verify(program) {
+        Ok(()) => println!("replay matches"),
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        }
+    }
+}
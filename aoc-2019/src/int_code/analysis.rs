@@ -0,0 +1,306 @@
+use super::disassembler::{disassemble, reachable, successors, Line};
+use super::instruction::{decode, AddressingMode, Command};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+// A run of instructions that is only entered at the top and only left at the bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    // One past the last cell of the last instruction.
+    pub end: usize,
+    pub instructions: Vec<(usize, Command)>,
+    // The blocks execution may continue in.
+    pub successors: Vec<usize>,
+    // Whether the block ends in a jump whose target is only known at run time, so that
+    // `successors` may be missing some.
+    pub dynamic: bool,
+}
+
+impl Block {
+    fn _last(&self) -> Command {
+        self.instructions
+            .last()
+            .map(|(_, command)| *command)
+            .expect("blocks aren't empty")
+    }
+
+    pub fn halts(&self) -> bool {
+        self._last() == Command::End()
+    }
+}
+
+// An instruction that writes to a cell holding (part of) a reachable instruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SelfModification {
+    pub address: usize,
+    pub target: usize,
+    // The instruction the written cell belongs to.
+    pub modifies: usize,
+}
+
+// What can be worked out about a program without running it. Only jumps with immediate
+// targets are followed, and only writes to position operands are checked, since relative
+// and indirect addresses depend on run time state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    pub blocks: BTreeMap<usize, Block>,
+    pub self_modifications: Vec<SelfModification>,
+    // Instructions that decode cleanly but that no path from address 0 reaches.
+    pub unreachable: Vec<(usize, Command)>,
+}
+
+fn _is_jump(command: Command) -> bool {
+    matches!(
+        command,
+        Command::JmpIfTrue(..) | Command::JmpIfFalse(..) | Command::End()
+    )
+}
+
+fn _is_dynamic(command: Command) -> bool {
+    match command {
+        Command::JmpIfTrue(test, target) | Command::JmpIfFalse(test, target) => {
+            let never = matches!(
+                (command, test),
+                (Command::JmpIfTrue(..), AddressingMode::Immediate(0))
+            ) || matches!(
+                (command, test),
+                (Command::JmpIfFalse(..), AddressingMode::Immediate(value)) if value != 0
+            );
+            !never && !matches!(target, AddressingMode::Immediate(_))
+        }
+        _ => false,
+    }
+}
+
+// Splits decoded instructions into basic blocks. Blocks start at the entry point, at jump
+// targets and after jumps or gaps in the code.
+pub fn blocks(code: &BTreeMap<usize, Command>) -> BTreeMap<usize, Block> {
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    let mut previous_end = None;
+    for (address, command) in code {
+        if _is_jump(*command) {
+            leaders.extend(successors(*address, *command));
+            leaders.insert(address + command.length());
+        }
+        if previous_end != Some(*address) {
+            leaders.insert(*address);
+        }
+        previous_end = Some(address + command.length());
+    }
+
+    let mut blocks: BTreeMap<usize, Block> = BTreeMap::new();
+    let mut current: Option<Block> = None;
+    for (address, command) in code {
+        if leaders.contains(address) {
+            if let Some(block) = current.take() {
+                blocks.insert(block.start, block);
+            }
+        }
+        let block = current.get_or_insert_with(|| Block {
+            start: *address,
+            end: *address,
+            instructions: Vec::new(),
+            successors: Vec::new(),
+            dynamic: false,
+        });
+        block.instructions.push((*address, *command));
+        block.end = address + command.length();
+        block.successors = successors(*address, *command)
+            .into_iter()
+            .filter(|next| code.contains_key(next))
+            .collect();
+        block.dynamic = _is_dynamic(*command);
+    }
+    if let Some(block) = current {
+        blocks.insert(block.start, block);
+    }
+    blocks
+}
+
+// The instructions in `code` that write to a position inside `code`.
+pub fn self_modifications(code: &BTreeMap<usize, Command>) -> Vec<SelfModification> {
+    let mut owner = BTreeMap::new();
+    for (address, command) in code {
+        for cell in *address..address + command.length() {
+            owner.insert(cell, *address);
+        }
+    }
+    code.iter()
+        .filter_map(|(address, command)| match command.target() {
+            Some(AddressingMode::Register(target)) => {
+                owner.get(&target).map(|modifies| SelfModification {
+                    address: *address,
+                    target,
+                    modifies: *modifies,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Addresses that execution reaches from `code` and that the machine would run, but that
+// `reachable` left out: instructions with stray mode digits, ones running past the end of
+// the program and ones overlapping another instruction.
+pub fn unrecognised(program: &[i64], code: &BTreeMap<usize, Command>) -> Vec<usize> {
+    let targets: BTreeSet<usize> = std::iter::once(0)
+        .chain(
+            code.iter()
+                .flat_map(|(address, command)| successors(*address, *command)),
+        )
+        .collect();
+    targets
+        .into_iter()
+        .filter(|address| !code.contains_key(address))
+        .filter(|address| *address < program.len() && decode(&program[*address..]).is_ok())
+        .collect()
+}
+
+pub fn analyse(program: &[i64]) -> Analysis {
+    let code = reachable(program);
+    let unreachable = disassemble(program)
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::Instruction {
+                address,
+                command,
+                reachable: false,
+            } => Some((address, command)),
+            _ => None,
+        })
+        .collect();
+    Analysis {
+        blocks: blocks(&code),
+        self_modifications: self_modifications(&code),
+        unreachable,
+    }
+}
+
+impl Analysis {
+    // The control flow graph in Graphviz DOT. Blocks containing self-modifying writes are
+    // drawn in red, and dynamic jumps point at a `?` node.
+    pub fn to_dot(&self) -> String {
+        let writers: BTreeSet<usize> = self
+            .self_modifications
+            .iter()
+            .map(|modification| modification.address)
+            .collect();
+        let mut dot = String::from("digraph intcode {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for (address, command) in &block.instructions {
+                write!(label, "{}: {}\\l", address, command).expect("writing to a string");
+            }
+            let modifies = block
+                .instructions
+                .iter()
+                .any(|(address, _)| writers.contains(address));
+            let colour = if modifies { ", color=red" } else { "" };
+            writeln!(dot, "    b{} [label=\"{}\"{}];", block.start, label, colour)
+                .expect("writing to a string");
+        }
+        if self.blocks.values().any(|block| block.dynamic) {
+            dot.push_str("    dynamic [shape=ellipse, label=\"?\"];\n");
+        }
+        for block in self.blocks.values() {
+            for next in &block.successors {
+                writeln!(dot, "    b{} -> b{};", block.start, next).expect("writing to a string");
+            }
+            if block.dynamic {
+                writeln!(dot, "    b{} -> dynamic [style=dashed];", block.start)
+                    .expect("writing to a string");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::*;
+
+    // counts down from an input, patching the output instruction's operand to point at a
+    // second counter first.
+    fn program() -> Vec<i64> {
+        assemble(
+            "
+                    in [n]
+                    add #m, #0, [print+1]
+            loop:   jf [n], #end
+            print:  out [n]
+                    add [n], #-1, [n]
+                    jt #1, #loop
+            end:    jt #1, [n]
+                    mul #2, #2, [n]
+                    hlt
+            n:      data 0
+            m:      data 7
+            ",
+        )
+        .expect("failed to assemble")
+    }
+
+    #[test]
+    fn blocks() {
+        let analysis = analyse(&program());
+        let starts: Vec<usize> = analysis.blocks.keys().copied().collect();
+        assert_eq!(starts, vec![0, 6, 9, 18]);
+        let entry = &analysis.blocks[&0];
+        assert_eq!(entry.instructions.len(), 2);
+        assert_eq!(entry.successors, vec![6]);
+        assert_eq!(analysis.blocks[&6].successors, vec![9, 18]);
+        assert_eq!(analysis.blocks[&9].successors, vec![6]);
+        let end = &analysis.blocks[&18];
+        assert!(end.dynamic);
+        assert!(end.successors.is_empty());
+        assert!(!end.halts());
+    }
+
+    #[test]
+    fn self_modification_and_dead_code() {
+        let analysis = analyse(&program());
+        assert_eq!(
+            analysis.self_modifications,
+            vec![SelfModification {
+                address: 2,
+                target: 10,
+                modifies: 9
+            }]
+        );
+        let unreachable: Vec<usize> = analysis.unreachable.iter().map(|(a, _)| *a).collect();
+        assert_eq!(unreachable, vec![21, 25]);
+    }
+
+    #[test]
+    fn unrecognised_code() {
+        // a stray mode digit, an output running off the end and a jump into an operand.
+        for (program, expected) in [
+            (vec![10104, 7, 99], vec![0]),
+            (vec![1105, 1, 3, 4], vec![3]),
+            (vec![1105, 1, 2, 99], vec![2]),
+            (vec![104, 7, 99], vec![]),
+        ] {
+            let code = reachable(&program);
+            assert_eq!(unrecognised(&program, &code), expected, "{:?}", program);
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let analysis = analyse(&[1105, 1, 4, 99, 99]);
+        assert_eq!(
+            analysis.to_dot(),
+            "digraph intcode {\n    \
+                 node [shape=box, fontname=\"monospace\"];\n    \
+                 b0 [label=\"0: jt #1, #4\\l\"];\n    \
+                 b4 [label=\"4: hlt\\l\"];\n    \
+                 b0 -> b4;\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::machine::{ExecutionState, Machine, MachineError};
+use std::collections::VecDeque;
+
+// Everything a program output between two requests for input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Response {
+    pub text: String,
+    // Values outside the ASCII range, which programs use to report their answers.
+    pub answers: Vec<i64>,
+    pub halted: bool,
+}
+
+pub fn is_ascii(value: i64) -> bool {
+    (0..128).contains(&value)
+}
+
+// The character codes for a line of input, including the newline that ends it.
+pub fn encode_line(line: &str) -> Vec<i64> {
+    line.trim_end_matches(['\r', '\n'])
+        .bytes()
+        .chain(Some(b'\n'))
+        .map(i64::from)
+        .collect()
+}
+
+// Talks to a program that reads and writes text one line at a time.
+#[derive(Clone, Debug)]
+pub struct AsciiMachine {
+    machine: Machine<VecDeque<i64>, Vec<i64>>,
+}
+
+impl AsciiMachine {
+    pub fn new(program: Vec<i64>) -> Self {
+        AsciiMachine {
+            machine: Machine::with_program(program),
+        }
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        for value in encode_line(line) {
+            self.machine.push_input(value);
+        }
+    }
+
+    // Runs until the program wants more input than has been sent, or halts.
+    pub fn run(&mut self) -> Result<Response, MachineError> {
+        let mut response = Response::default();
+        loop {
+            match self.machine.run_until_io()? {
+                ExecutionState::Output(value) if is_ascii(value) => {
+                    response.text.push(value as u8 as char)
+                }
+                ExecutionState::Output(value) => response.answers.push(value),
+                ExecutionState::Halted => {
+                    response.halted = true;
+                    break;
+                }
+                _ => break,
+            }
+        }
+        self.machine.output_mut().clear();
+        Ok(response)
+    }
+
+    pub fn machine(&self) -> &Machine<VecDeque<i64>, Vec<i64>> {
+        &self.machine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::*;
+
+    #[test]
+    fn echo_line() {
+        let program = assemble(
+            "
+            loop:   in [c]
+                    out [c]
+                    eq [c], #10, [t]
+                    jf [t], #loop
+                    out #1000
+                    hlt
+            c:      data 0
+            t:      data 0
+            ",
+        )
+        .expect("failed to assemble");
+        let mut machine = AsciiMachine::new(program);
+        assert_eq!(machine.run(), Ok(Response::default()));
+        machine.send_line("hello\r\n");
+        assert_eq!(
+            machine.run(),
+            Ok(Response {
+                text: String::from("hello\n"),
+                answers: vec![1000],
+                halted: true,
+            })
+        );
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(encode_line("A,B"), vec![65, 44, 66, 10]);
+        assert_eq!(encode_line(""), vec![10]);
+    }
+}
@@ -0,0 +1,327 @@
+use super::instruction::{AddressingMode, Command};
+use std::collections::HashMap;
+
+// Assembles the textual syntax produced by the disassembler:
+//
+//     ; comments run to the end of the line
+//     start: in [count]          ; `name:` defines a label for the next address
+//     loop:  out [count]
+//            add [count], #-1, [count]
+//            jt [count], #loop
+//            arb #10
+//            out rb-1            ; relative operands are `rb+x` or `rb-x`
+//            hlt
+//     count: data 0, 1, -2
+//
+// Operand values are integers, labels or `label+n`/`label-n`. A numeric label such as `12:`
+// asserts the current address instead of defining a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Number(i64),
+    Label(String, i64),
+}
+
+#[derive(Clone, Debug)]
+enum Operand {
+    Position(Value),
+    Immediate(Value),
+    Relative(Value),
+}
+
+#[derive(Clone, Debug)]
+enum Statement {
+    Instruction(String, Vec<Operand>),
+    Data(Vec<Value>),
+}
+
+fn _operand_count(mnemonic: &str) -> Option<usize> {
+    match mnemonic {
+        "add" | "mul" | "lt" | "eq" => Some(3),
+        "jt" | "jf" => Some(2),
+        "in" | "out" | "arb" => Some(1),
+        "hlt" => Some(0),
+        _ => None,
+    }
+}
+
+fn _is_label(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn _parse_value(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    if let Ok(number) = text.parse::<i64>() {
+        return Ok(Value::Number(number));
+    }
+    let (name, offset) = match text.find(['+', '-']) {
+        Some(split) => {
+            let offset = text[split..]
+                .replace(' ', "")
+                .parse::<i64>()
+                .map_err(|_| format!("bad offset in '{}'", text))?;
+            (text[..split].trim(), offset)
+        }
+        None => (text, 0),
+    };
+    if _is_label(name) {
+        Ok(Value::Label(name.to_string(), offset))
+    } else {
+        Err(format!("expected a number or label, found '{}'", text))
+    }
+}
+
+fn _parse_operand(text: &str) -> Result<Operand, String> {
+    let text = text.trim();
+    if text.starts_with('[') && text.ends_with(']') {
+        Ok(Operand::Position(_parse_value(&text[1..text.len() - 1])?))
+    } else if let Some(value) = text.strip_prefix('#') {
+        Ok(Operand::Immediate(_parse_value(value)?))
+    } else if let Some(offset) = text.strip_prefix("rb") {
+        let offset = offset.trim();
+        if offset.is_empty() {
+            Ok(Operand::Relative(Value::Number(0)))
+        } else if let Some(value) = offset.strip_prefix('+') {
+            Ok(Operand::Relative(_parse_value(value)?))
+        } else if offset.starts_with('-') {
+            match _parse_value(offset)? {
+                Value::Number(number) => Ok(Operand::Relative(Value::Number(number))),
+                Value::Label(..) => Err(format!("can't negate a label in '{}'", text)),
+            }
+        } else {
+            Err(format!("bad relative operand '{}'", text))
+        }
+    } else {
+        Err(format!("operand '{}' needs a mode: [x], #x or rb+x", text))
+    }
+}
+
+fn _split_list(text: &str) -> Vec<&str> {
+    if text.trim().is_empty() {
+        Vec::new()
+    } else {
+        text.split(',').map(|item| item.trim()).collect()
+    }
+}
+
+fn _parse_statement(text: &str) -> Result<Statement, String> {
+    let (word, rest) = match text.find(char::is_whitespace) {
+        Some(split) => (&text[..split], &text[split..]),
+        None => (text, ""),
+    };
+    let mnemonic = word.to_lowercase();
+    if mnemonic == "data" {
+        let values = _split_list(rest)
+            .into_iter()
+            .map(_parse_value)
+            .collect::<Result<Vec<Value>, String>>()?;
+        return Ok(Statement::Data(values));
+    }
+    let count = _operand_count(&mnemonic).ok_or_else(|| format!("unknown mnemonic '{}'", word))?;
+    let operands = _split_list(rest)
+        .into_iter()
+        .map(_parse_operand)
+        .collect::<Result<Vec<Operand>, String>>()?;
+    if operands.len() != count {
+        return Err(format!(
+            "'{}' takes {} operands, found {}",
+            mnemonic,
+            count,
+            operands.len()
+        ));
+    }
+    Ok(Statement::Instruction(mnemonic, operands))
+}
+
+fn _resolve(value: &Value, labels: &HashMap<String, usize>) -> Result<i64, String> {
+    match value {
+        Value::Number(number) => Ok(*number),
+        Value::Label(name, offset) => labels
+            .get(name)
+            .map(|address| *address as i64 + offset)
+            .ok_or_else(|| format!("undefined label '{}'", name)),
+    }
+}
+
+fn _addressing_mode(
+    operand: &Operand,
+    labels: &HashMap<String, usize>,
+) -> Result<AddressingMode, String> {
+    match operand {
+        Operand::Position(value) => match _resolve(value, labels)? {
+            address if address < 0 => Err(format!("negative address {}", address)),
+            address => Ok(AddressingMode::Register(address as usize)),
+        },
+        Operand::Immediate(value) => Ok(AddressingMode::Immediate(_resolve(value, labels)?)),
+        Operand::Relative(value) => Ok(AddressingMode::Relative(_resolve(value, labels)?)),
+    }
+}
+
+fn _command(mnemonic: &str, operands: &[AddressingMode]) -> Command {
+    match (mnemonic, operands) {
+        ("add", [a, b, c]) => Command::Add(*a, *b, *c),
+        ("mul", [a, b, c]) => Command::Multiply(*a, *b, *c),
+        ("lt", [a, b, c]) => Command::LessThan(*a, *b, *c),
+        ("eq", [a, b, c]) => Command::Equal(*a, *b, *c),
+        ("jt", [a, b]) => Command::JmpIfTrue(*a, *b),
+        ("jf", [a, b]) => Command::JmpIfFalse(*a, *b),
+        ("in", [a]) => Command::IoRead(*a),
+        ("out", [a]) => Command::IoWrite(*a),
+        ("arb", [a]) => Command::AdjustRelativeBase(*a),
+        ("hlt", []) => Command::End(),
+        _ => unreachable!("operand count is checked when parsing"),
+    }
+}
+
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssembleError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: String| AssembleError {
+            line: number + 1,
+            message,
+        };
+        let mut text = line.split(';').next().unwrap_or("").trim();
+        while let Some(split) = text.find(':') {
+            let label = text[..split].trim();
+            if let Ok(expected) = label.parse::<usize>() {
+                if expected != address {
+                    return Err(error(format!(
+                        "expected address {} but assembling at {}",
+                        expected, address
+                    )));
+                }
+            } else if _is_label(label) {
+                if labels.insert(label.to_string(), address).is_some() {
+                    return Err(error(format!("label '{}' defined twice", label)));
+                }
+            } else {
+                return Err(error(format!("bad label '{}'", label)));
+            }
+            text = text[split + 1..].trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let statement = _parse_statement(text).map_err(error)?;
+        address += match &statement {
+            Statement::Instruction(_, operands) => operands.len() + 1,
+            Statement::Data(values) => values.len(),
+        };
+        statements.push((number + 1, statement));
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for (line, statement) in statements {
+        let error = |message: String| AssembleError { line, message };
+        match statement {
+            Statement::Data(values) => {
+                for value in values {
+                    program.push(_resolve(&value, &labels).map_err(error)?);
+                }
+            }
+            Statement::Instruction(mnemonic, operands) => {
+                let operands = operands
+                    .iter()
+                    .map(|operand| _addressing_mode(operand, &labels))
+                    .collect::<Result<Vec<AddressingMode>, String>>()
+                    .map_err(error)?;
+                let command = _command(&mnemonic, &operands);
+                if let Some(AddressingMode::Immediate(_)) = command.target() {
+                    return Err(error(format!(
+                        "'{}' can't write to an immediate operand",
+                        mnemonic
+                    )));
+                }
+                program.extend(command.encode());
+            }
+        }
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::disassembler::listing;
+    use super::super::machine::Machine;
+    use super::*;
+
+    #[test]
+    fn day5_example_2_round_trip() {
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        assert_eq!(assemble(&listing(&program)), Ok(program));
+    }
+
+    #[test]
+    fn countdown() {
+        let program = assemble(
+            "
+            ; print n, n-1, ..., 1
+                    in [count]
+            loop:   out [count]
+                    add [count], #-1, [count]
+                    jt [count], #loop
+                    arb #stack
+                    out rb-1
+                    hlt
+            count:  data 0
+                    data 7
+            stack:
+            ",
+        )
+        .expect("failed to assemble");
+        let mut machine = Machine::new(program, vec![3], Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output(), vec![3, 2, 1, 7]);
+    }
+
+    #[test]
+    fn labels_with_offsets() {
+        assert_eq!(
+            assemble("a: jf #0, #b+1\nb: data a, b-1, 5"),
+            Ok(vec![1106, 0, 4, 0, 2, 5])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for (source, line) in [
+            ("hlt\nfoo [1]", 2),
+            ("add [1], [2]", 1),
+            ("add [1], [2], #3", 1),
+            ("jt #1, #missing", 1),
+            ("x: hlt\nx: hlt", 2),
+            ("hlt\n0: hlt", 2),
+            ("out 5", 1),
+            ("out [-1]", 1),
+        ] {
+            assert_eq!(assemble(source).expect_err(source).line, line);
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use super::analysis::{blocks, self_modifications, unrecognised, Block, SelfModification};
+use super::disassembler::{disassemble, reachable, Line};
+use super::instruction::{AddressingMode, Command};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+// Translates a program into a Rust module with a `run` function that executes it natively:
+//
+//     pub trait Io {
+//         fn input(&mut self) -> Option<i64>;
+//         fn output(&mut self, value: i64);
+//     }
+//     pub fn run<T: Io>(io: &mut T) -> Result<(), Error>
+//
+// Every instruction the disassembler finds is compiled, reached or not, since return
+// addresses are usually only jumped to indirectly. Each basic block becomes an arm of a
+// `match` on the address of the next block, so direct jumps and dynamic ones look the same.
+//
+// Compiled code can't follow a program that rewrites its own instructions. Reachable
+// instructions writing to fixed addresses inside reachable code are refused here, and any
+// other write to reachable code fails when the compiled program runs. Unreached
+// instructions are often data that only happens to decode, so writing to them is allowed,
+// but a block is checked against the original program before it runs once one has been
+// written. Code the disassembler doesn't recognise, such as instructions with stray mode
+// digits, is refused too, since it would be compiled as data and escape these checks.
+//
+// Arithmetic fails with `Overflow` where the interpreter's would.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileError {
+    SelfModifying(Vec<SelfModification>),
+    // The addresses of reachable instructions that couldn't be compiled.
+    Unrecognised(Vec<usize>),
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CompileError::SelfModifying(modifications) => {
+                let writes: Vec<String> = modifications
+                    .iter()
+                    .map(|m| format!("{} writes {} in {}", m.address, m.target, m.modifies))
+                    .collect();
+                write!(f, "program modifies its own code: {}", writes.join(", "))
+            }
+            CompileError::Unrecognised(addresses) => {
+                let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+                write!(
+                    f,
+                    "can't compile the instructions at {}",
+                    addresses.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+static PRELUDE: &str = "\
+#![cfg_attr(rustfmt, rustfmt::skip)]
+#![allow(clippy::all, dead_code, unused_mut, unused_parens, unused_variables)]
+
+pub trait Io {
+    fn input(&mut self) -> Option<i64>;
+    fn output(&mut self, value: i64);
+}
+
+// Each error carries the address of the instruction that failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    InputClosed(usize),
+    NegativeAddress(usize, i64),
+    // The program wrote to one of its reachable instructions.
+    CodeWrite(usize, usize),
+    // The block at the address was about to run after the program had written over it.
+    ModifiedCode(usize),
+    Overflow(usize),
+    // Execution continued somewhere that isn't a compiled block.
+    BadJump(i64),
+}
+
+// Also whether the program has written to any unreached code.
+struct Memory(Vec<i64>, bool);
+
+impl Memory {
+    #[inline]
+    fn read(&self, at: usize, address: i64) -> Result<i64, Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        Ok(self.0.get(address as usize).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    fn write(&mut self, at: usize, address: i64, value: i64) -> Result<(), Error> {
+        if address < 0 {
+            return Err(Error::NegativeAddress(at, address));
+        }
+        let address = address as usize;
+        if is_code(address) {
+            return Err(Error::CodeWrite(at, address));
+        }
+        if is_unreached(address) {
+            self.1 = true;
+        }
+        if address >= self.0.len() {
+            self.0.resize(address + 1, 0);
+        }
+        self.0[address] = value;
+        Ok(())
+    }
+
+    #[inline]
+    fn check(&self, start: usize, end: usize) -> Result<(), Error> {
+        if self.1 && self.0[start..end] != PROGRAM[start..end] {
+            return Err(Error::ModifiedCode(start));
+        }
+        Ok(())
+    }
+}
+";
+
+// An expression for the value of an operand of the instruction at `at`.
+fn _read(operand: AddressingMode, at: usize, length: usize) -> String {
+    match operand {
+        // memory never shrinks, so addresses inside the program can be indexed directly.
+        AddressingMode::Register(address) if address < length => format!("memory.0[{}]", address),
+        AddressingMode::Register(address) => format!("memory.read({}, {})?", at, address),
+        AddressingMode::Immediate(value) => format!("({})", value),
+        AddressingMode::Relative(offset) => format!("memory.read({}, rb + ({}))?", at, offset),
+    }
+}
+
+// A statement storing `value` to an operand. Only fixed addresses outside the code are
+// written directly.
+fn _write(operand: AddressingMode, at: usize, program: &Program, value: &str) -> String {
+    match operand {
+        AddressingMode::Register(address)
+            if address < program.length && !program.cells.contains(&address) =>
+        {
+            format!("memory.0[{}] = {};", address, value)
+        }
+        AddressingMode::Register(address) => {
+            format!("memory.write({}, {}, {})?;", at, address, value)
+        }
+        AddressingMode::Relative(offset) => {
+            format!("memory.write({}, rb + ({}), {})?;", at, offset, value)
+        }
+        AddressingMode::Immediate(_) => unreachable!("decoding rejects immediate targets"),
+    }
+}
+
+// An expression applying a checked `i64` operation.
+fn _checked(operation: &str, a: &str, b: &str, at: usize) -> String {
+    format!(
+        "i64::checked_{}({}, {}).ok_or(Error::Overflow({}))?",
+        operation, a, b, at
+    )
+}
+
+// The statements for one instruction. The last instruction of a block also gives the
+// expression for the next block.
+fn _instruction(out: &mut String, at: usize, command: Command, program: &Program, block: &Block) {
+    let read = |operand| _read(operand, at, program.length);
+    let write = |operand, value: &str| _write(operand, at, program, value);
+    let next = at + command.length();
+    let line = match command {
+        Command::Add(a, b, c) => write(c, &_checked("add", &read(a), &read(b), at)),
+        Command::Multiply(a, b, c) => write(c, &_checked("mul", &read(a), &read(b), at)),
+        Command::LessThan(a, b, c) => write(c, &format!("({} < {}) as i64", read(a), read(b))),
+        Command::Equal(a, b, c) => write(c, &format!("({} == {}) as i64", read(a), read(b))),
+        Command::IoRead(a) => {
+            let input = format!("io.input().ok_or(Error::InputClosed({}))?", at);
+            write(a, &input)
+        }
+        Command::IoWrite(a) => format!("io.output({});", read(a)),
+        Command::AdjustRelativeBase(a) => format!("rb = {};", _checked("add", "rb", &read(a), at)),
+        Command::JmpIfTrue(test, target) | Command::JmpIfFalse(test, target) => {
+            let jump = match command {
+                Command::JmpIfTrue(..) => "!=",
+                _ => "==",
+            };
+            format!(
+                "if {} {} 0 {{ {} }} else {{ {} }}",
+                read(test),
+                jump,
+                read(target),
+                next
+            )
+        }
+        Command::End() => String::from("return Ok(());"),
+    };
+    let last = block.instructions.last().map(|(address, _)| *address) == Some(at);
+    let ends_block = matches!(
+        command,
+        Command::JmpIfTrue(..) | Command::JmpIfFalse(..) | Command::End()
+    );
+    writeln!(out, "                // {}: {}", at, command).expect("writing to a string");
+    writeln!(out, "                {}", line).expect("writing to a string");
+    if last && !ends_block {
+        writeln!(out, "                {}", next).expect("writing to a string");
+    }
+}
+
+// The length of the program being compiled and the cells its instructions occupy.
+struct Program {
+    length: usize,
+    cells: BTreeSet<usize>,
+}
+
+// A function called `name` matching the ranges of cells the instructions in `code` occupy.
+fn _contains(out: &mut String, name: &str, code: &BTreeMap<usize, Command>) {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (address, command) in code {
+        let end = address + command.length();
+        match ranges.last_mut() {
+            Some((_, last)) if *last == *address => *last = end,
+            _ => ranges.push((*address, end)),
+        }
+    }
+    let ranges: Vec<String> = ranges
+        .iter()
+        .map(|(start, end)| format!("{}..={}", start, end - 1))
+        .collect();
+    writeln!(out, "\nfn {}(address: usize) -> bool {{", name).expect("writing to a string");
+    if ranges.is_empty() {
+        out.push_str("    let _ = address;\n    false\n");
+    } else {
+        writeln!(out, "    matches!(address, {})", ranges.join(" | "))
+            .expect("writing to a string");
+    }
+    out.push_str("}\n");
+}
+
+pub fn compile(program: &[i64]) -> Result<String, CompileError> {
+    let reached = reachable(program);
+    let unrecognised = unrecognised(program, &reached);
+    if !unrecognised.is_empty() {
+        return Err(CompileError::Unrecognised(unrecognised));
+    }
+    let modifications = self_modifications(&reached);
+    if !modifications.is_empty() {
+        return Err(CompileError::SelfModifying(modifications));
+    }
+    let code: BTreeMap<usize, Command> = disassemble(program)
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::Instruction {
+                address, command, ..
+            } => Some((address, command)),
+            Line::Data { .. } => None,
+        })
+        .collect();
+    let unreached: BTreeMap<usize, Command> = code
+        .iter()
+        .filter(|(address, _)| !reached.contains_key(address))
+        .map(|(address, command)| (*address, *command))
+        .collect();
+    let compiled = Program {
+        length: program.len(),
+        cells: code
+            .iter()
+            .flat_map(|(address, command)| *address..address + command.length())
+            .collect(),
+    };
+
+    let mut out = format!(
+        "// Generated by intcode-compile from a program of {} values. Do not edit.\n",
+        program.len()
+    );
+    out.push_str(PRELUDE);
+    _contains(&mut out, "is_code", &reached);
+    _contains(&mut out, "is_unreached", &unreached);
+    let values: Vec<String> = program.iter().map(|value| value.to_string()).collect();
+    writeln!(
+        out,
+        "\nstatic PROGRAM: [i64; {}] = [{}];",
+        program.len(),
+        values.join(", ")
+    )
+    .expect("writing to a string");
+
+    out.push_str(
+        "
+pub fn run<T: Io>(io: &mut T) -> Result<(), Error> {
+    let mut memory = Memory(PROGRAM.to_vec(), false);
+    let mut rb: i64 = 0;
+    let mut block: i64 = 0;
+    loop {
+        block = match block {
+",
+    );
+    for block in blocks(&code).values() {
+        writeln!(out, "            {} => {{", block.start).expect("writing to a string");
+        let checked = block
+            .instructions
+            .iter()
+            .any(|(address, _)| unreached.contains_key(address));
+        if checked {
+            writeln!(
+                out,
+                "                memory.check({}, {})?;",
+                block.start, block.end
+            )
+            .expect("writing to a string");
+        }
+        for (address, command) in &block.instructions {
+            _instruction(&mut out, *address, *command, &compiled, block);
+        }
+        out.push_str("            }\n");
+    }
+    out.push_str(
+        "            other => return Err(Error::BadJump(other)),
+        };
+    }
+}
+",
+    );
+    Ok(out)
+}
+
+// The compiled day 9 BOOST program, which the interpreter benchmark also uses.
+#[cfg(test)]
+#[path = "../../fixtures/boost.rs"]
+mod boost;
+
+// The compiled `fixtures/overflow.intcode`.
+#[cfg(test)]
+#[path = "../../fixtures/overflow.rs"]
+mod overflow;
+
+#[cfg(test)]
+mod tests {
+    use super::super::machine::{Machine, MachineErrorKind};
+    use super::super::read_fixture;
+    use super::*;
+
+    use super::{boost, overflow};
+
+    struct Buffers(Vec<i64>, Vec<i64>);
+
+    impl boost::Io for Buffers {
+        fn input(&mut self) -> Option<i64> {
+            match self.0.is_empty() {
+                true => None,
+                false => Some(self.0.remove(0)),
+            }
+        }
+
+        fn output(&mut self, value: i64) {
+            self.1.push(value);
+        }
+    }
+
+    impl overflow::Io for Buffers {
+        fn input(&mut self) -> Option<i64> {
+            boost::Io::input(self)
+        }
+
+        fn output(&mut self, value: i64) {
+            boost::Io::output(self, value)
+        }
+    }
+
+    #[test]
+    fn compiled_fixtures_are_up_to_date() {
+        for (fixture, generated) in [
+            ("day9.intcode", "fixtures/boost.rs"),
+            ("overflow.intcode", "fixtures/overflow.rs"),
+        ] {
+            let source = compile(&read_fixture(fixture)).expect("failed to compile");
+            let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), generated);
+            let generated_source = std::fs::read_to_string(&path).expect("failed to read");
+            assert!(
+                source == generated_source,
+                "regenerate with: cargo run --bin intcode-compile fixtures/{} > {}",
+                fixture,
+                generated
+            );
+        }
+    }
+
+    #[test]
+    fn compiled_matches_interpreter() {
+        let program = read_fixture("day9.intcode");
+        for input in [1, 2] {
+            let mut machine = Machine::new(program.clone(), vec![input], Vec::new());
+            machine.execute().expect("failed to execute");
+            let mut buffers = Buffers(vec![input], Vec::new());
+            boost::run(&mut buffers).expect("failed to run compiled program");
+            assert_eq!(buffers.1, machine.into_output());
+        }
+        let mut buffers = Buffers(Vec::new(), Vec::new());
+        assert_eq!(boost::run(&mut buffers), Err(boost::Error::InputClosed(25)));
+
+        let program = read_fixture("overflow.intcode");
+        for input in [1, -2, 2] {
+            let mut machine = Machine::new(program.clone(), vec![input], Vec::new());
+            let result = machine.execute();
+            let mut buffers = Buffers(vec![input], Vec::new());
+            let compiled = overflow::run(&mut buffers);
+            match result {
+                Ok(()) => assert_eq!(compiled, Ok(())),
+                Err(error) => {
+                    assert_eq!(error.kind, MachineErrorKind::Overflow);
+                    assert_eq!(
+                        compiled,
+                        Err(overflow::Error::Overflow(error.program_counter))
+                    );
+                }
+            }
+            assert_eq!(buffers.1, machine.into_output(), "input {}", input);
+        }
+    }
+
+    #[test]
+    fn writes_to_unreached_code() {
+        // the fixture's product goes in the data after its halt, which decodes as an add.
+        // `overflow` is this program compiled, kept current by the test above.
+        let program = read_fixture("overflow.intcode");
+        assert_eq!(program[9], 1);
+        assert!(compile(&program).is_ok());
+        let mut buffers = Buffers(vec![1], Vec::new());
+        assert_eq!(overflow::run(&mut buffers), Ok(()));
+        assert_eq!(buffers.1, vec![1 << 62]);
+
+        // jumps indirectly into its data after writing to it.
+        let source =
+            compile(&[1101, 5, 0, 7, 105, 1, 11, 1, 0, 0, 0, 7]).expect("failed to compile");
+        assert!(source.contains("            7 => {\n                memory.check(7, 11)?;\n"));
+        assert!(source.contains("memory.write(0, 7, "));
+    }
+
+    #[test]
+    fn refuses_self_modifying_code() {
+        // overwrites the halt at 4 with itself.
+        let error = compile(&[1101, 99, 0, 4, 99]).expect_err("compiled self-modifying code");
+        assert_eq!(
+            error,
+            CompileError::SelfModifying(vec![SelfModification {
+                address: 0,
+                target: 4,
+                modifies: 4
+            }])
+        );
+        assert!(compile(&[1101, 99, 0, 5, 99, 0]).is_ok());
+    }
+
+    #[test]
+    fn refuses_unrecognised_code() {
+        // the add has a stray mode digit for its target, so it would otherwise be data and
+        // its write to the halt would go unnoticed.
+        let error = compile(&[101101, 99, 0, 4, 99]).expect_err("compiled unrecognised code");
+        assert_eq!(error, CompileError::Unrecognised(vec![0]));
+        assert_eq!(error.to_string(), "can't compile the instructions at 0");
+    }
+
+    #[test]
+    fn generated_blocks() {
+        let source = compile(&[3, 7, 1005, 7, 0, 99, 0, 0]).expect("failed to compile");
+        assert!(source.contains("matches!(address, 0..=5)"));
+        assert!(source.contains(
+            "            0 => {\n                \
+                 // 0: in [7]\n                \
+                 memory.0[7] = io.input().ok_or(Error::InputClosed(0))?;\n                \
+                 // 2: jt [7], #0\n                \
+                 if memory.0[7] != 0 { (0) } else { 5 }\n            \
+             }\n"
+        ));
+    }
+}
@@ -0,0 +1,599 @@
+use super::assembler::assemble;
+use super::machine::{Machine, MachineErrorKind};
+use super::memory::DEFAULT_MEMORY_LIMIT;
+use super::read_fixture;
+use std::collections::VecDeque;
+
+// Checks the machine against a table of small programs, and against a reference interpreter
+// on the fixtures and on randomly generated programs. Every run is made with the decode cache
+// both on and off, so self-modifying code must behave the same either way.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    Halted { memory: Vec<i64>, output: Vec<i64> },
+    Failed,
+    OutOfFuel,
+}
+
+// A plain interpreter written from the puzzle descriptions, sharing no code with the machine.
+// It decodes as strictly as the machine does, rejecting bad modes and negative position
+// operands before executing anything, but doesn't say why an instruction failed.
+struct Reference {
+    memory: Vec<i64>,
+    pc: i64,
+    rb: i64,
+    input: VecDeque<i64>,
+    output: Vec<i64>,
+}
+
+impl Reference {
+    fn fetch(&self, address: i64) -> i64 {
+        self.memory.get(address as usize).copied().unwrap_or(0)
+    }
+
+    fn check(&self, address: i64) -> Option<usize> {
+        match address {
+            address if address < 0 || address as usize >= DEFAULT_MEMORY_LIMIT => None,
+            address => Some(address as usize),
+        }
+    }
+
+    fn mode(&self, n: u32) -> i64 {
+        self.fetch(self.pc) / 10i64.pow(n + 1) % 10
+    }
+
+    // The address of parameter `n`, which mustn't be immediate.
+    fn address(&self, n: u32) -> Option<usize> {
+        let raw = self.fetch(self.pc + n as i64);
+        match self.mode(n) {
+            0 => self.check(raw),
+            2 => self.check(self.rb.checked_add(raw)?),
+            _ => None,
+        }
+    }
+
+    fn read(&self, n: u32) -> Option<i64> {
+        match self.mode(n) {
+            1 => Some(self.fetch(self.pc + n as i64)),
+            _ => Some(self.fetch(self.address(n)? as i64)),
+        }
+    }
+
+    fn write(&mut self, n: u32, value: i64) -> Option<()> {
+        let address = self.address(n)?;
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+        Some(())
+    }
+
+    // Executes one instruction, returning whether it was a halt.
+    fn step(&mut self) -> Option<bool> {
+        let opcode = self.fetch(self.pc) % 100;
+        let (reads, writes) = match opcode {
+            1 | 2 | 7 | 8 => (2, 1),
+            3 => (0, 1),
+            4 | 9 => (1, 0),
+            5 | 6 => (2, 0),
+            99 => return Some(true),
+            _ => return None,
+        };
+        for n in 1..=reads + writes {
+            let raw = self.fetch(self.pc + n as i64);
+            match self.mode(n) {
+                0 if raw < 0 => return None,
+                1 if n > reads => return None,
+                0..=2 => {}
+                _ => return None,
+            }
+        }
+        let mut next = self.pc + 1 + reads as i64 + writes as i64;
+        match opcode {
+            1 => {
+                let sum = self.read(1)?.checked_add(self.read(2)?)?;
+                self.write(3, sum)?;
+            }
+            2 => {
+                let product = self.read(1)?.checked_mul(self.read(2)?)?;
+                self.write(3, product)?;
+            }
+            3 => {
+                self.address(1)?;
+                let value = self.input.pop_front()?;
+                self.write(1, value)?;
+            }
+            4 => {
+                let value = self.read(1)?;
+                self.output.push(value);
+            }
+            5 | 6 => {
+                if (self.read(1)? != 0) == (opcode == 5) {
+                    next = self.read(2)?;
+                    if next < 0 {
+                        return None;
+                    }
+                }
+            }
+            7 => {
+                let less = self.read(1)? < self.read(2)?;
+                self.write(3, less as i64)?;
+            }
+            8 => {
+                let equal = self.read(1)? == self.read(2)?;
+                self.write(3, equal as i64)?;
+            }
+            _ => self.rb = self.rb.checked_add(self.read(1)?)?,
+        }
+        self.pc = next;
+        Some(false)
+    }
+}
+
+fn reference(program: &[i64], input: &[i64], fuel: usize) -> Outcome {
+    let mut machine = Reference {
+        memory: program.to_vec(),
+        pc: 0,
+        rb: 0,
+        input: input.iter().copied().collect(),
+        output: Vec::new(),
+    };
+    for _ in 0..fuel {
+        match machine.step() {
+            Some(true) => {
+                return Outcome::Halted {
+                    memory: machine.memory,
+                    output: machine.output,
+                }
+            }
+            Some(false) => {}
+            None => return Outcome::Failed,
+        }
+    }
+    Outcome::OutOfFuel
+}
+
+fn execute(
+    program: &[i64],
+    input: &[i64],
+    decode_cache: bool,
+) -> Result<(Vec<i64>, Vec<i64>), MachineErrorKind> {
+    let mut machine = Machine::new(
+        program.to_vec(),
+        input.iter().copied().collect::<VecDeque<i64>>(),
+        Vec::new(),
+    )
+    .with_decode_cache(decode_cache);
+    machine.execute().map_err(|error| error.kind)?;
+    Ok((machine.read_memory().clone(), machine.into_output()))
+}
+
+// Runs `program` on the machine, with and without the decode cache, and on the reference
+// interpreter, checking that all three agree. Returns the machine's result.
+fn compare(
+    program: &[i64],
+    input: &[i64],
+    fuel: usize,
+) -> Option<Result<Vec<i64>, MachineErrorKind>> {
+    let expected = reference(program, input, fuel);
+    if expected == Outcome::OutOfFuel {
+        return None;
+    }
+    let result = execute(program, input, true);
+    assert_eq!(
+        result,
+        execute(program, input, false),
+        "decode cache changed the result of {:?}",
+        program
+    );
+    let outcome = match &result {
+        Ok((memory, output)) => Outcome::Halted {
+            memory: memory.clone(),
+            output: output.clone(),
+        },
+        Err(_) => Outcome::Failed,
+    };
+    assert_eq!(
+        outcome, expected,
+        "machine and reference differ on {:?}",
+        program
+    );
+    Some(result.map(|(_, output)| output))
+}
+
+struct Case {
+    name: &'static str,
+    source: &'static str,
+    input: &'static [i64],
+    expected: Result<&'static [i64], MachineErrorKind>,
+}
+
+static CASES: &[Case] = &[
+    Case {
+        name: "add in every mode",
+        source: "
+                    arb #20
+                    add [a], #3, rb+1
+                    add rb+1, [a], [b]
+                    out rb+1
+                    out [b]
+                    hlt
+            a:      data 4
+            b:      data 0
+        ",
+        input: &[],
+        expected: Ok(&[7, 11]),
+    },
+    Case {
+        name: "mul in every mode",
+        source: "
+                    arb #20
+                    mul [a], #-3, rb+1
+                    mul rb+1, rb+1, [b]
+                    out rb+1
+                    out [b]
+                    hlt
+            a:      data 4
+            b:      data 0
+        ",
+        input: &[],
+        expected: Ok(&[-12, 144]),
+    },
+    Case {
+        name: "in to position and relative",
+        source: "
+                    arb #30
+                    in [a]
+                    in rb+2
+                    out [a]
+                    out rb+2
+                    hlt
+            a:      data 0
+        ",
+        input: &[8, 9],
+        expected: Ok(&[8, 9]),
+    },
+    Case {
+        name: "out in every mode",
+        source: "
+                    out [a]
+                    out #-7
+                    arb #a
+                    out rb+0
+                    hlt
+            a:      data 42
+        ",
+        input: &[],
+        expected: Ok(&[42, -7, 42]),
+    },
+    Case {
+        name: "jt in every mode",
+        source: "
+                    jt #0, #fail
+                    jt #-5, [p1]
+            fail:   out #0
+                    hlt
+            pass1:  arb #one
+                    jt rb+0, rb+1
+                    hlt
+            pass2:  out #1
+                    hlt
+            one:    data 1, pass2
+            p1:     data pass1
+        ",
+        input: &[],
+        expected: Ok(&[1]),
+    },
+    Case {
+        name: "jf in every mode",
+        source: "
+                    jf #1, #fail
+                    jf [zero], [p1]
+            fail:   out #0
+                    hlt
+            pass1:  arb #zero
+                    jf rb+0, rb+1
+                    hlt
+            pass2:  out #2
+                    hlt
+            zero:   data 0, pass2
+            p1:     data pass1
+        ",
+        input: &[],
+        expected: Ok(&[2]),
+    },
+    Case {
+        name: "lt in every mode",
+        source: "
+                    arb #40
+                    lt #1, #2, [r]
+                    lt #2, #1, rb+0
+                    lt [a], [a], [s]
+                    lt #-3, [a], rb+1
+                    out [r]
+                    out rb+0
+                    out [s]
+                    out rb+1
+                    hlt
+            a:      data -2
+            r:      data 7
+            s:      data 7
+        ",
+        input: &[],
+        expected: Ok(&[1, 0, 0, 1]),
+    },
+    Case {
+        name: "eq in every mode",
+        source: "
+                    arb #40
+                    eq #5, #5, [r]
+                    eq [a], #5, rb+0
+                    eq rb+0, #0, [s]
+                    out [r]
+                    out rb+0
+                    out [s]
+                    hlt
+            a:      data -2
+            r:      data 7
+            s:      data 7
+        ",
+        input: &[],
+        expected: Ok(&[1, 0, 1]),
+    },
+    Case {
+        name: "arb in every mode",
+        source: "
+                    arb #v
+                    arb rb+1
+                    arb [neg]
+                    out rb+0
+                    hlt
+            v:      data 7, 2
+            neg:    data -1
+        ",
+        input: &[],
+        expected: Ok(&[2]),
+    },
+    Case {
+        name: "relative base reaches back to 0",
+        source: "
+                    arb #10
+                    out rb-10
+                    arb #-10
+                    out rb+0
+                    hlt
+        ",
+        input: &[],
+        expected: Ok(&[109, 109]),
+    },
+    Case {
+        name: "relative base below 0",
+        source: "
+                    arb #-1
+                    out rb+1
+                    out rb+0
+                    hlt
+        ",
+        input: &[],
+        expected: Err(MachineErrorKind::NegativeAddress(-1)),
+    },
+    Case {
+        name: "relative base beyond the program",
+        source: "
+                    arb #1000
+                    add #6, #7, rb+5
+                    out rb+5
+                    out rb+6
+                    hlt
+        ",
+        input: &[],
+        expected: Ok(&[13, 0]),
+    },
+    Case {
+        name: "relative base overflow",
+        source: "
+                    arb #9223372036854775807
+                    arb #1
+                    hlt
+        ",
+        input: &[],
+        expected: Err(MachineErrorKind::Overflow),
+    },
+    Case {
+        name: "rewrites an instruction it has already run",
+        source: "
+            loop:   out [v]
+                    jt [done], #end
+                    add #1, #0, [done]
+                    add #104, #0, [loop]
+                    jt #1, #loop
+            end:    hlt
+            v:      data 55
+            done:   data 0
+        ",
+        input: &[],
+        expected: Ok(&[55, 17]),
+    },
+    Case {
+        name: "rewrites an operand",
+        source: "
+                    add #7, #0, [print+1]
+            print:  out #0
+                    hlt
+        ",
+        input: &[],
+        expected: Ok(&[7]),
+    },
+    Case {
+        name: "overwrites the running instruction",
+        source: "
+                    in [0]
+                    out [0]
+                    hlt
+        ",
+        input: &[5],
+        expected: Ok(&[5]),
+    },
+    Case {
+        name: "writes its own halt",
+        source: "
+                    add #99, #0, [end]
+            end:    data 0
+        ",
+        input: &[],
+        expected: Ok(&[]),
+    },
+    Case {
+        name: "unknown opcode",
+        source: "data 42",
+        input: &[],
+        expected: Err(MachineErrorKind::UnknownOpcode(42)),
+    },
+    Case {
+        name: "unknown addressing mode",
+        source: "data 304, 0",
+        input: &[],
+        expected: Err(MachineErrorKind::InvalidAddressingMode {
+            operand: 1,
+            mode: 3,
+        }),
+    },
+    Case {
+        name: "immediate target",
+        source: "data 11101, 1, 1, 0",
+        input: &[],
+        expected: Err(MachineErrorKind::WriteToImmediate { operand: 3 }),
+    },
+    Case {
+        name: "negative position",
+        source: "data 4, -1",
+        input: &[],
+        expected: Err(MachineErrorKind::NegativeAddress(-1)),
+    },
+    Case {
+        name: "negative jump",
+        source: "jt #1, #-4",
+        input: &[],
+        expected: Err(MachineErrorKind::NegativeAddress(-4)),
+    },
+    Case {
+        name: "input closed",
+        source: "in [0]",
+        input: &[],
+        expected: Err(MachineErrorKind::InputClosed),
+    },
+    Case {
+        name: "overflow",
+        source: "mul #9223372036854775807, #2, [0]",
+        input: &[],
+        expected: Err(MachineErrorKind::Overflow),
+    },
+];
+
+#[test]
+fn conformance_table() {
+    for case in CASES {
+        let program = assemble(case.source).expect("failed to assemble");
+        let result = compare(&program, case.input, 10_000).expect("ran out of fuel");
+        assert_eq!(
+            result,
+            case.expected.map(|output| output.to_vec()),
+            "{}",
+            case.name
+        );
+    }
+}
+
+#[test]
+fn fixtures_match_reference() {
+    for (fixture, input, last) in [
+        ("day5.intcode", 1, 3122865),
+        ("day5.intcode", 5, 773660),
+        ("day9.intcode", 1, 3906448201),
+        ("day9.intcode", 2, 59785),
+    ] {
+        let program = read_fixture(fixture);
+        let output = compare(&program, &[input], 1 << 24)
+            .expect("ran out of fuel")
+            .expect("failed to execute");
+        assert_eq!(
+            output.last(),
+            Some(&last),
+            "{} with input {}",
+            fixture,
+            input
+        );
+    }
+}
+
+// xorshift64, so that failures are reproducible from the seed.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: i64) -> i64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as i64
+    }
+
+    fn between(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below(high - low + 1)
+    }
+}
+
+// Mostly well formed instructions with operands near the program, so that jumps, writes into
+// the code and the relative base all come up often. Now and then a value is left unchecked.
+fn random_program(random: &mut Random) -> Vec<i64> {
+    let length = random.between(16, 48);
+    let mut program = Vec::new();
+    while (program.len() as i64) < length {
+        let opcode = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99][random.below(10) as usize];
+        let operands = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            3 | 4 | 9 => 1,
+            5 | 6 => 2,
+            _ => 0,
+        };
+        let mut instruction = opcode;
+        for n in 0..operands {
+            let mode = match random.below(40) {
+                0 => 3,
+                roll => roll % 3,
+            };
+            instruction += mode * 10i64.pow(n + 2);
+        }
+        program.push(instruction);
+        for _ in 0..operands {
+            program.push(match random.below(20) {
+                0 => random.between(-1000, 1000),
+                _ => random.between(-4, length + 4),
+            });
+        }
+    }
+    program
+}
+
+#[test]
+fn random_programs_match_reference() {
+    let mut random = Random(0x1c0de);
+    let (mut halted, mut failed) = (0, 0);
+    for _ in 0..3000 {
+        let program = random_program(&mut random);
+        let input: Vec<i64> = (0..random.below(4))
+            .map(|_| random.between(-2, 30))
+            .collect();
+        match compare(&program, &input, 2000) {
+            Some(Ok(_)) => halted += 1,
+            Some(Err(_)) => failed += 1,
+            None => {}
+        }
+    }
+    // the generator should exercise both outcomes.
+    assert!(
+        halted > 100 && failed > 100,
+        "{} halted, {} failed",
+        halted,
+        failed
+    );
+}
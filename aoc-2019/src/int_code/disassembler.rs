@@ -0,0 +1,248 @@
+use super::instruction::{decode, AddressingMode, Command};
+use std::collections::{BTreeMap, VecDeque};
+
+static DATA_PER_LINE: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Line {
+    // `reachable` is false for instructions only found by the linear sweep.
+    Instruction {
+        address: usize,
+        command: Command,
+        reachable: bool,
+    },
+    Data {
+        address: usize,
+        values: Vec<i64>,
+    },
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Line::Instruction {
+                address,
+                command,
+                reachable,
+            } => {
+                write!(f, "{:>5}: {}", address, command)?;
+                if !reachable {
+                    write!(f, "  ; unreached")?;
+                }
+                Ok(())
+            }
+            Line::Data { address, values } => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "{:>5}: data {}", address, values.join(", "))
+            }
+        }
+    }
+}
+
+// Decodes the instruction at `address` only if it fits in the program and re-encodes to the
+// same words, so that the listing assembles back to the original program.
+fn _decode_at(program: &[i64], address: usize) -> Option<Command> {
+    let (command, length) = decode(&program[address..]).ok()?;
+    if address + length <= program.len()
+        && command.encode()[..] == program[address..address + length]
+    {
+        Some(command)
+    } else {
+        None
+    }
+}
+
+// The addresses execution may continue at after `command`, leaving out jumps whose target
+// isn't known until run time.
+pub fn successors(address: usize, command: Command) -> Vec<usize> {
+    let next = address + command.length();
+    let (test, target, jump_when) = match command {
+        Command::End() => return vec![],
+        Command::JmpIfTrue(test, target) => (test, target, true),
+        Command::JmpIfFalse(test, target) => (test, target, false),
+        _ => return vec![next],
+    };
+    let mut successors = Vec::new();
+    let (may_jump, may_fall_through) = match test {
+        AddressingMode::Immediate(value) => ((value != 0) == jump_when, (value != 0) != jump_when),
+        _ => (true, true),
+    };
+    if may_fall_through {
+        successors.push(next);
+    }
+    if let (true, AddressingMode::Immediate(target)) = (may_jump, target) {
+        if target >= 0 {
+            successors.push(target as usize);
+        }
+    }
+    successors
+}
+
+// Finds the instructions reachable from address 0 by following fall-through and jumps with
+// immediate targets.
+pub fn reachable(program: &[i64]) -> BTreeMap<usize, Command> {
+    let mut owner: Vec<Option<usize>> = vec![None; program.len()];
+    let mut code = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+    while let Some(address) = queue.pop_front() {
+        if address >= program.len() || owner[address].is_some() {
+            continue;
+        }
+        let command = match _decode_at(program, address) {
+            Some(command) => command,
+            None => continue,
+        };
+        let cells = address..address + command.length();
+        if cells.clone().any(|cell| owner[cell].is_some()) {
+            continue;
+        }
+        for cell in cells {
+            owner[cell] = Some(address);
+        }
+        code.insert(address, command);
+        queue.extend(successors(address, command));
+    }
+    code
+}
+
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    let code = reachable(program);
+    let mut lines = Vec::new();
+    let mut data: Option<(usize, Vec<i64>)> = None;
+    let mut address = 0;
+    while address < program.len() {
+        let next_code = code
+            .range(address..)
+            .next()
+            .map(|(next, _)| *next)
+            .unwrap_or_else(|| program.len());
+        let found = match code.get(&address) {
+            Some(command) => Some((*command, true)),
+            None => _decode_at(program, address)
+                .filter(|command| address + command.length() <= next_code)
+                .map(|command| (command, false)),
+        };
+        match found {
+            Some((command, reachable)) => {
+                if let Some((start, values)) = data.take() {
+                    lines.push(Line::Data {
+                        address: start,
+                        values,
+                    });
+                }
+                lines.push(Line::Instruction {
+                    address,
+                    command,
+                    reachable,
+                });
+                address += command.length();
+            }
+            None => {
+                let (_, values) = data.get_or_insert_with(|| (address, Vec::new()));
+                values.push(program[address]);
+                if values.len() == DATA_PER_LINE {
+                    let (start, values) = data.take().expect("data was just inserted");
+                    lines.push(Line::Data {
+                        address: start,
+                        values,
+                    });
+                }
+                address += 1;
+            }
+        }
+    }
+    if let Some((start, values)) = data {
+        lines.push(Line::Data {
+            address: start,
+            values,
+        });
+    }
+    lines
+}
+
+pub fn listing(program: &[i64]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day9_example1() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_eq!(
+            listing(&program),
+            "    0: arb #1\n    \
+                 2: out rb-1\n    \
+                 4: add [100], #1, [100]\n    \
+                 8: eq [100], #16, [101]\n   \
+                12: jf [101], #0\n   \
+                15: hlt\n"
+        );
+    }
+
+    #[test]
+    fn data_after_halt() {
+        let program = vec![1105, 1, 7, 0, 0, 0, -5, 4, 3, 99, 1101, 1, 1, 0, 1, 2];
+        let lines = disassemble(&program);
+        assert_eq!(
+            lines,
+            vec![
+                Line::Instruction {
+                    address: 0,
+                    command: Command::JmpIfTrue(
+                        AddressingMode::Immediate(1),
+                        AddressingMode::Immediate(7)
+                    ),
+                    reachable: true,
+                },
+                Line::Data {
+                    address: 3,
+                    values: vec![0, 0, 0, -5],
+                },
+                Line::Instruction {
+                    address: 7,
+                    command: Command::IoWrite(AddressingMode::Register(3)),
+                    reachable: true,
+                },
+                Line::Instruction {
+                    address: 9,
+                    command: Command::End(),
+                    reachable: true,
+                },
+                Line::Instruction {
+                    address: 10,
+                    command: Command::Add(
+                        AddressingMode::Immediate(1),
+                        AddressingMode::Immediate(1),
+                        AddressingMode::Register(0)
+                    ),
+                    reachable: false,
+                },
+                Line::Data {
+                    address: 14,
+                    values: vec![1, 2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn non_canonical_encoding_is_data() {
+        let program = vec![10099, 11104, 1];
+        assert_eq!(
+            disassemble(&program),
+            vec![Line::Data {
+                address: 0,
+                values: vec![10099, 11104, 1],
+            }]
+        );
+    }
+}
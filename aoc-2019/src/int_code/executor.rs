@@ -0,0 +1,431 @@
+use super::io::{Input, Output};
+use super::machine::{ExecutionState, Machine, MachineError, MachineErrorKind};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+// Instructions a machine runs before giving way to other tasks.
+static YIELD_INTERVAL: usize = 1000;
+
+// Runs machines as futures, so that any number of them can share one thread:
+//
+//     let (input, machine_input) = channel();
+//     let (machine_output, mut output) = channel();
+//     let mut executor = Executor::new();
+//     let machine = executor.spawn(run(Machine::new(program, machine_input, machine_output)));
+//     executor.spawn(async move {
+//         input.send(1);
+//         while let Some(value) = output.recv().await { ... }
+//     });
+//     executor.run()?;
+//
+// Channels and tasks aren't Send, as everything runs on the thread that calls `run`.
+#[derive(Debug)]
+struct Channel {
+    queue: VecDeque<i64>,
+    senders: usize,
+    receiver: bool,
+    waker: Option<Waker>,
+}
+
+impl Channel {
+    fn _wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+pub fn channel() -> (AsyncSender, AsyncReceiver) {
+    let channel = Rc::new(RefCell::new(Channel {
+        queue: VecDeque::new(),
+        senders: 1,
+        receiver: true,
+        waker: None,
+    }));
+    (
+        AsyncSender {
+            channel: channel.clone(),
+            closed: false,
+        },
+        AsyncReceiver { channel },
+    )
+}
+
+// Sending never waits, as channels are unbounded.
+#[derive(Debug)]
+pub struct AsyncSender {
+    channel: Rc<RefCell<Channel>>,
+    closed: bool,
+}
+
+impl AsyncSender {
+    // Returns false if the receiver is gone or this sender has been closed.
+    pub fn send(&self, value: i64) -> bool {
+        let mut channel = self.channel.borrow_mut();
+        if self.closed || !channel.receiver {
+            return false;
+        }
+        channel.queue.push_back(value);
+        channel._wake();
+        true
+    }
+
+    // Ends the stream once every sender is closed or dropped.
+    pub fn close(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            let mut channel = self.channel.borrow_mut();
+            channel.senders -= 1;
+            channel._wake();
+        }
+    }
+}
+
+impl Clone for AsyncSender {
+    fn clone(&self) -> Self {
+        if !self.closed {
+            self.channel.borrow_mut().senders += 1;
+        }
+        AsyncSender {
+            channel: self.channel.clone(),
+            closed: self.closed,
+        }
+    }
+}
+
+impl Drop for AsyncSender {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl Output for AsyncSender {
+    fn write(&mut self, value: i64) -> bool {
+        self.send(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct AsyncReceiver {
+    channel: Rc<RefCell<Channel>>,
+}
+
+impl AsyncReceiver {
+    pub fn try_recv(&mut self) -> Option<i64> {
+        self.channel.borrow_mut().queue.pop_front()
+    }
+
+    // Waits until a value can be read, returning false if none ever will be.
+    pub fn readable(&mut self) -> Readable<'_> {
+        Readable(self)
+    }
+
+    // The next value, or None once the stream has ended.
+    pub async fn recv(&mut self) -> Option<i64> {
+        match self.readable().await {
+            true => self.try_recv(),
+            false => None,
+        }
+    }
+}
+
+impl Drop for AsyncReceiver {
+    fn drop(&mut self) {
+        self.channel.borrow_mut().receiver = false;
+    }
+}
+
+// Never blocks, so an empty channel stops the machine with `NeedsInput`.
+impl Input for AsyncReceiver {
+    fn read(&mut self) -> Option<i64> {
+        self.try_recv()
+    }
+}
+
+pub struct Readable<'a>(&'a AsyncReceiver);
+
+impl Future for Readable<'_> {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<bool> {
+        let mut channel = self.0.channel.borrow_mut();
+        if !channel.queue.is_empty() {
+            Poll::Ready(true)
+        } else if channel.senders == 0 {
+            Poll::Ready(false)
+        } else {
+            channel.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+// Gives way to the other tasks once.
+pub fn yield_now() -> impl Future<Output = ()> {
+    let mut yielded = false;
+    std::future::poll_fn(move |cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+}
+
+pub type AsyncMachine = Machine<AsyncReceiver, AsyncSender>;
+
+// Runs a machine to completion, waiting on its input channel whenever it needs a value and
+// giving way to other tasks every `YIELD_INTERVAL` instructions. The machine's output is
+// closed when it stops, so readers see the end of the stream. Values left in the input
+// channel can be read from the returned machine.
+pub async fn run(mut machine: AsyncMachine) -> Result<AsyncMachine, MachineError> {
+    let result = _run(&mut machine).await;
+    machine.output_mut().close();
+    result.map(|_| machine)
+}
+
+async fn _run(machine: &mut AsyncMachine) -> Result<(), MachineError> {
+    loop {
+        for _ in 0..YIELD_INTERVAL {
+            match machine.step()? {
+                ExecutionState::Halted => return Ok(()),
+                // nothing more will arrive, so fail as a blocking machine would.
+                ExecutionState::NeedsInput if !machine.input_mut().readable().await => {
+                    return Err(machine.error(MachineErrorKind::InputClosed));
+                }
+                _ => {}
+            }
+        }
+        yield_now().await;
+    }
+}
+
+// The tasks left when nothing could make progress, all waiting on channels that will never
+// receive anything.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stalled {
+    pub tasks: usize,
+}
+
+impl std::fmt::Display for Stalled {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} tasks are waiting and can't continue", self.tasks)
+    }
+}
+
+impl std::error::Error for Stalled {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+// The result of a spawned task, once `Executor::run` has finished it.
+#[derive(Debug)]
+pub struct JoinHandle<T>(Rc<RefCell<Option<T>>>);
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    pub fn take(&self) -> Option<T> {
+        self.0.borrow_mut().take()
+    }
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+// Wakes a task by queueing its index to be polled.
+struct Wakeup {
+    task: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for Wakeup {
+    fn wake(self: Arc<Self>) {
+        self.ready
+            .lock()
+            .expect("ready queue lock poisoned")
+            .push_back(self.task);
+    }
+}
+
+// Polls tasks in the order they are woken, on the calling thread.
+#[derive(Default)]
+pub struct Executor {
+    tasks: Vec<Option<Task>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Executor::default()
+    }
+
+    pub fn spawn<F, T>(&mut self, future: F) -> JoinHandle<T>
+    where
+        F: Future<Output = T> + 'static,
+        T: 'static,
+    {
+        let result = Rc::new(RefCell::new(None));
+        let slot = result.clone();
+        self.tasks.push(Some(Box::pin(async move {
+            *slot.borrow_mut() = Some(future.await);
+        })));
+        self.ready
+            .lock()
+            .expect("ready queue lock poisoned")
+            .push_back(self.tasks.len() - 1);
+        JoinHandle(result)
+    }
+
+    // Runs until every task has finished, or until the remaining ones are all waiting.
+    pub fn run(&mut self) -> Result<(), Stalled> {
+        loop {
+            let next = self
+                .ready
+                .lock()
+                .expect("ready queue lock poisoned")
+                .pop_front();
+            let index = match next {
+                Some(index) => index,
+                None => break,
+            };
+            // a task woken more than once may already have finished.
+            if let Some(task) = &mut self.tasks[index] {
+                let waker = Waker::from(Arc::new(Wakeup {
+                    task: index,
+                    ready: self.ready.clone(),
+                }));
+                if task
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_ready()
+                {
+                    self.tasks[index] = None;
+                }
+            }
+        }
+        match self.tasks.iter().filter(|task| task.is_some()).count() {
+            0 => Ok(()),
+            tasks => Err(Stalled { tasks }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::super::machine::MachineErrorKind;
+    use super::*;
+
+    // reads a value, adds one and passes it on until it has passed on `limit`.
+    fn increment(limit: i64) -> Vec<i64> {
+        assemble(&format!(
+            "
+            loop:   in [x]
+                    add [x], #1, [x]
+                    out [x]
+                    lt [x], #{}, [c]
+                    jt [c], #loop
+                    hlt
+            x:      data 0
+            c:      data 0
+            ",
+            limit
+        ))
+        .expect("failed to assemble")
+    }
+
+    #[test]
+    fn hundreds_of_machines() {
+        let mut executor = Executor::new();
+        let (first, mut input) = channel();
+        let mut machines = Vec::new();
+        for _ in 0..500 {
+            let (output, next) = channel();
+            let machine = Machine::new(increment(1), input, output);
+            machines.push(executor.spawn(run(machine)));
+            input = next;
+        }
+        let last = executor.spawn(async move { input.recv().await });
+        first.send(0);
+        executor.run().expect("stalled");
+        assert_eq!(last.take(), Some(Some(500)));
+        assert!(machines
+            .iter()
+            .all(|machine| matches!(machine.take(), Some(Ok(_)))));
+    }
+
+    #[test]
+    fn feedback_loop() {
+        // day 7's second example, with each amplifier feeding the next.
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        let phases = [9, 8, 7, 6, 5];
+        let (senders, receivers): (Vec<_>, Vec<_>) = phases.iter().map(|_| channel()).unzip();
+        for (sender, phase) in senders.iter().zip(phases) {
+            sender.send(phase);
+        }
+        senders[0].send(0);
+        let mut executor = Executor::new();
+        let amplifiers: Vec<_> = receivers
+            .into_iter()
+            .enumerate()
+            .map(|(i, input)| {
+                let output = senders[(i + 1) % senders.len()].clone();
+                executor.spawn(run(Machine::new(program.clone(), input, output)))
+            })
+            .collect();
+        drop(senders);
+        executor.run().expect("stalled");
+        let mut first = amplifiers[0]
+            .take()
+            .expect("not finished")
+            .expect("failed to execute");
+        assert_eq!(first.input_mut().try_recv(), Some(139_629_729));
+    }
+
+    #[test]
+    fn stalls_and_errors() {
+        // waits for input that never comes while its sender is still alive.
+        let (input, machine_input) = channel();
+        let (output, _) = channel();
+        let mut executor = Executor::new();
+        let waiting = executor.spawn(run(Machine::new(vec![3, 0, 99], machine_input, output)));
+        assert_eq!(executor.run(), Err(Stalled { tasks: 1 }));
+        assert!(!waiting.is_finished());
+
+        // closing its input wakes it up to fail.
+        drop(input);
+        let (_, machine_input) = channel();
+        let (output, mut stream) = channel();
+        let closed = executor.spawn(run(Machine::new(
+            vec![104, 7, 3, 0, 99],
+            machine_input,
+            output,
+        )));
+        let read = executor.spawn(async move { (stream.recv().await, stream.recv().await) });
+        executor.run().expect("stalled");
+        let error = waiting
+            .take()
+            .expect("not finished")
+            .expect_err("input closed");
+        assert_eq!(error.kind, MachineErrorKind::InputClosed);
+        let error = closed
+            .take()
+            .expect("not finished")
+            .expect_err("input closed");
+        assert_eq!(error.kind, MachineErrorKind::InputClosed);
+        assert_eq!(read.take(), Some((Some(7), None)));
+    }
+}
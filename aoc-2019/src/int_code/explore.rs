@@ -0,0 +1,299 @@
+use super::machine::{ExecutionState, Machine, MachineError};
+use super::snapshot::Snapshot;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+// Instructions a fork may run from one state to the next before its branch is given up on.
+static STEPS: usize = 1_000_000;
+
+// A state the program stopped in, waiting for input or halted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    pub snapshot: Snapshot,
+    // The inputs that lead here from the start.
+    pub inputs: Vec<i64>,
+    // What the program output after the last input.
+    pub outputs: Vec<i64>,
+    pub halted: bool,
+}
+
+// What to do after visiting a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    // Don't try any inputs from this node.
+    Prune,
+    // Stop searching and return this node.
+    Stop,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exploration {
+    // The number of distinct states visited.
+    pub states: usize,
+    // The number of branches given up on because they ran out of steps.
+    pub exhausted: usize,
+    // The branches whose machine failed, and the inputs that lead to each failure.
+    pub faults: Vec<(Vec<i64>, MachineError)>,
+    pub found: Option<Node>,
+}
+
+// Searches the states a program can reach breadth first. From each state every input value is
+// tried on a fork of the machine, which runs until it wants more input or halts. States are
+// told apart by a hash of their memory and registers, so a program that returns to a state it
+// has been in before isn't explored again.
+#[derive(Clone, Debug)]
+pub struct Explorer {
+    start: Snapshot,
+    inputs: Vec<i64>,
+    limit: Option<usize>,
+    steps: usize,
+}
+
+fn _hash(snapshot: &Snapshot) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    snapshot.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Runs a machine until it needs input or halts, returning the new node, or None if that
+// takes more than `steps` instructions.
+fn _run(
+    snapshot: Snapshot,
+    inputs: Vec<i64>,
+    input: Option<i64>,
+    steps: usize,
+) -> Result<Option<Node>, MachineError> {
+    let mut machine = Machine::from_snapshot(snapshot, VecDeque::new(), Vec::new());
+    if let Some(value) = input {
+        machine.push_input(value);
+    }
+    let mut halted = None;
+    for _ in 0..steps {
+        match machine.step()? {
+            ExecutionState::Halted => halted = Some(true),
+            ExecutionState::NeedsInput => halted = Some(false),
+            _ => continue,
+        }
+        break;
+    }
+    Ok(halted.map(|halted| Node {
+        snapshot: machine.snapshot(),
+        inputs,
+        outputs: machine.into_output(),
+        halted,
+    }))
+}
+
+impl Explorer {
+    pub fn new(program: Vec<i64>, inputs: &[i64]) -> Self {
+        let machine = Machine::with_program(program);
+        Explorer::from_snapshot(machine.snapshot(), inputs)
+    }
+
+    // Starts from a machine part way through a run.
+    pub fn from_snapshot(snapshot: Snapshot, inputs: &[i64]) -> Self {
+        Explorer {
+            start: snapshot,
+            inputs: inputs.to_vec(),
+            limit: None,
+            steps: STEPS,
+        }
+    }
+
+    // Gives up after visiting `states` distinct states.
+    pub fn with_limit(mut self, states: usize) -> Self {
+        self.limit = Some(states);
+        self
+    }
+
+    // Gives up on a branch if it runs more than `steps` instructions without halting or
+    // asking for input.
+    pub fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    // Calls `visit` with each new state in breadth first order, starting with the state the
+    // program reaches before it first asks for input. Only a failure reaching that first state
+    // is returned as an error; a branch that fails later is dropped and kept in `faults`.
+    pub fn run<F>(&self, mut visit: F) -> Result<Exploration, MachineError>
+    where
+        F: FnMut(&Node) -> Visit,
+    {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        let mut exhausted = 0;
+        let mut faults = Vec::new();
+        let start = _run(self.start.clone(), Vec::new(), None, self.steps)?;
+        match start {
+            Some(node) => queue.push_back(node),
+            None => exhausted += 1,
+        }
+        while let Some(node) = queue.pop_front() {
+            if !seen.insert(_hash(&node.snapshot)) {
+                continue;
+            }
+            match visit(&node) {
+                Visit::Stop => {
+                    return Ok(Exploration {
+                        states: seen.len(),
+                        exhausted,
+                        faults,
+                        found: Some(node),
+                    })
+                }
+                Visit::Prune => continue,
+                Visit::Continue if node.halted => continue,
+                Visit::Continue => {}
+            }
+            if self.limit.is_some_and(|limit| seen.len() >= limit) {
+                break;
+            }
+            for input in &self.inputs {
+                let mut inputs = node.inputs.clone();
+                inputs.push(*input);
+                match _run(
+                    node.snapshot.clone(),
+                    inputs.clone(),
+                    Some(*input),
+                    self.steps,
+                ) {
+                    Ok(Some(next)) => queue.push_back(next),
+                    Ok(None) => exhausted += 1,
+                    Err(error) => faults.push((inputs, error)),
+                }
+            }
+        }
+        Ok(Exploration {
+            states: seen.len(),
+            exhausted,
+            faults,
+            found: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::super::machine::MachineErrorKind;
+    use super::*;
+
+    // moves a counter by each input and outputs where it is.
+    fn counter() -> Vec<i64> {
+        assemble(
+            "
+            loop:   in [d]
+                    add [pos], [d], [pos]
+                    add #0, #0, [d]
+                    out [pos]
+                    jt #1, #loop
+            pos:    data 0
+            d:      data 0
+            ",
+        )
+        .expect("failed to assemble")
+    }
+
+    #[test]
+    fn shortest_path() {
+        let explorer = Explorer::new(counter(), &[-1, 1]);
+        let exploration = explorer
+            .run(|node| match node.outputs.last() {
+                Some(3) => Visit::Stop,
+                _ => Visit::Continue,
+            })
+            .expect("failed to explore");
+        let found = exploration.found.expect("no path found");
+        assert_eq!(found.inputs, vec![1, 1, 1]);
+        assert_eq!(found.outputs, vec![3]);
+        assert!(!found.halted);
+    }
+
+    #[test]
+    fn deduplicates_states() {
+        let explorer = Explorer::new(counter(), &[-1, 1]);
+        let exploration = explorer
+            .run(|node| match node.outputs.last() {
+                Some(pos) if pos.abs() >= 3 => Visit::Prune,
+                _ => Visit::Continue,
+            })
+            .expect("failed to explore");
+        assert_eq!(exploration.states, 7);
+        assert_eq!(exploration.found, None);
+
+        let limited = Explorer::new(counter(), &[-1, 1]).with_limit(4);
+        let exploration = limited.run(|_| Visit::Continue).expect("failed to explore");
+        assert_eq!(exploration.states, 4);
+    }
+
+    #[test]
+    fn step_budget() {
+        // an input of 0 sends it into a loop that never asks for input again.
+        let program = assemble(
+            "
+            loop:   in [d]
+            spin:   jf [d], #spin
+                    out [d]
+                    jt #1, #loop
+            d:      data 0
+            ",
+        )
+        .expect("failed to assemble");
+        let explorer = Explorer::new(program, &[0, 1]).with_steps(100);
+        let exploration = explorer
+            .run(|node| match node.outputs.last() {
+                Some(1) => Visit::Stop,
+                _ => Visit::Continue,
+            })
+            .expect("failed to explore");
+        assert_eq!(exploration.exhausted, 1);
+        assert_eq!(exploration.found.expect("no path found").inputs, vec![1]);
+    }
+
+    #[test]
+    fn faulting_branches() {
+        // an input of 1 jumps into data.
+        let program = assemble(
+            "
+                    in [d]
+                    eq [d], #1, [t]
+                    jt [t], #bad
+                    out [d]
+                    hlt
+            bad:    data 42
+            d:      data 0
+            t:      data 0
+            ",
+        )
+        .expect("failed to assemble");
+        let exploration = Explorer::new(program, &[1, 2])
+            .run(|_| Visit::Continue)
+            .expect("failed to explore");
+        assert_eq!(exploration.states, 2);
+        let faults: Vec<(Vec<i64>, MachineErrorKind)> = exploration
+            .faults
+            .iter()
+            .map(|(inputs, error)| (inputs.clone(), error.kind))
+            .collect();
+        assert_eq!(faults, vec![(vec![1], MachineErrorKind::UnknownOpcode(42))]);
+    }
+
+    #[test]
+    fn from_snapshot() {
+        let mut machine = Machine::with_program(counter());
+        machine.push_input(2);
+        machine.run_until_io().expect("failed to execute");
+        let explorer = Explorer::from_snapshot(machine.snapshot(), &[1]);
+        let exploration = explorer
+            .run(|node| match node.outputs.last() {
+                Some(4) => Visit::Stop,
+                _ => Visit::Continue,
+            })
+            .expect("failed to explore");
+        let found = exploration.found.expect("no path found");
+        assert_eq!(found.inputs, vec![1, 1]);
+    }
+}
@@ -0,0 +1,227 @@
+use super::machine::MachineErrorKind;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AddressingMode {
+    Register(usize),
+    Immediate(i64),
+    Relative(i64),
+}
+
+impl AddressingMode {
+    pub fn mode(&self) -> i64 {
+        match self {
+            AddressingMode::Register(_) => 0,
+            AddressingMode::Immediate(_) => 1,
+            AddressingMode::Relative(_) => 2,
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        match *self {
+            AddressingMode::Register(pos) => pos as i64,
+            AddressingMode::Immediate(value) => value,
+            AddressingMode::Relative(offset) => offset,
+        }
+    }
+}
+
+impl std::fmt::Display for AddressingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            AddressingMode::Register(pos) => write!(f, "[{}]", pos),
+            AddressingMode::Immediate(value) => write!(f, "#{}", value),
+            AddressingMode::Relative(offset) if offset < 0 => write!(f, "rb{}", offset),
+            AddressingMode::Relative(offset) => write!(f, "rb+{}", offset),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    End(),
+    Add(AddressingMode, AddressingMode, AddressingMode),
+    Multiply(AddressingMode, AddressingMode, AddressingMode),
+    JmpIfTrue(AddressingMode, AddressingMode),
+    JmpIfFalse(AddressingMode, AddressingMode),
+    LessThan(AddressingMode, AddressingMode, AddressingMode),
+    Equal(AddressingMode, AddressingMode, AddressingMode),
+    IoRead(AddressingMode),
+    IoWrite(AddressingMode),
+    AdjustRelativeBase(AddressingMode),
+}
+
+impl Command {
+    pub fn opcode(&self) -> i64 {
+        match self {
+            Command::Add(..) => 1,
+            Command::Multiply(..) => 2,
+            Command::IoRead(..) => 3,
+            Command::IoWrite(..) => 4,
+            Command::JmpIfTrue(..) => 5,
+            Command::JmpIfFalse(..) => 6,
+            Command::LessThan(..) => 7,
+            Command::Equal(..) => 8,
+            Command::AdjustRelativeBase(..) => 9,
+            Command::End() => 99,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        mnemonic(self.opcode()).expect("every command has a mnemonic")
+    }
+
+    pub fn operands(&self) -> Vec<AddressingMode> {
+        match *self {
+            Command::End() => vec![],
+            Command::Add(a, b, c)
+            | Command::Multiply(a, b, c)
+            | Command::LessThan(a, b, c)
+            | Command::Equal(a, b, c) => vec![a, b, c],
+            Command::JmpIfTrue(a, b) | Command::JmpIfFalse(a, b) => vec![a, b],
+            Command::IoRead(a) | Command::IoWrite(a) | Command::AdjustRelativeBase(a) => vec![a],
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.operands().len() + 1
+    }
+
+    // The operand this command writes to, if any.
+    pub fn target(&self) -> Option<AddressingMode> {
+        match *self {
+            Command::Add(_, _, c)
+            | Command::Multiply(_, _, c)
+            | Command::LessThan(_, _, c)
+            | Command::Equal(_, _, c) => Some(c),
+            Command::IoRead(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    // The canonical encoding, with unused mode digits left as zero.
+    pub fn encode(&self) -> Vec<i64> {
+        let operands = self.operands();
+        let instruction = operands
+            .iter()
+            .enumerate()
+            .fold(self.opcode(), |instruction, (i, operand)| {
+                instruction + operand.mode() * 10i64.pow(i as u32 + 2)
+            });
+        let mut encoded = vec![instruction];
+        encoded.extend(operands.iter().map(|operand| operand.value()));
+        encoded
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, operand) in self.operands().iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, operand)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn mnemonic(opcode: i64) -> Option<&'static str> {
+    match opcode {
+        1 => Some("add"),
+        2 => Some("mul"),
+        3 => Some("in"),
+        4 => Some("out"),
+        5 => Some("jt"),
+        6 => Some("jf"),
+        7 => Some("lt"),
+        8 => Some("eq"),
+        9 => Some("arb"),
+        99 => Some("hlt"),
+        _ => None,
+    }
+}
+
+fn _generate_operation_vec(instruction: i64) -> (i64, i64, i64, i64) {
+    let opcode = instruction % 100;
+    let read_mode_1 = (instruction / 100) % 10;
+    let read_mode_2 = (instruction / 1000) % 10;
+    let read_mode_3 = (instruction / 10000) % 10;
+    (opcode, read_mode_1, read_mode_2, read_mode_3)
+}
+
+fn _create_addressing_mode(
+    operand: usize,
+    mode: i64,
+    value: i64,
+) -> Result<AddressingMode, MachineErrorKind> {
+    match mode {
+        0 if value < 0 => Err(MachineErrorKind::NegativeAddress(value)),
+        0 => Ok(AddressingMode::Register(value as usize)),
+        1 => Ok(AddressingMode::Immediate(value)),
+        2 => Ok(AddressingMode::Relative(value)),
+        _ => Err(MachineErrorKind::InvalidAddressingMode { operand, mode }),
+    }
+}
+
+fn _create_write_addressing_mode(
+    operand: usize,
+    mode: i64,
+    value: i64,
+) -> Result<AddressingMode, MachineErrorKind> {
+    match _create_addressing_mode(operand, mode, value)? {
+        AddressingMode::Immediate(_) => Err(MachineErrorKind::WriteToImmediate { operand }),
+        addressing_mode => Ok(addressing_mode),
+    }
+}
+
+// Decodes the instruction at the start of `slice`, returning it with its length. Missing
+// operands past the end of the slice read as 0.
+pub fn decode(slice: &[i64]) -> Result<(Command, usize), MachineErrorKind> {
+    let word = |i: usize| slice.get(i).copied().unwrap_or(0);
+    let op_vec = _generate_operation_vec(word(0));
+    let read = |operand: usize, mode| _create_addressing_mode(operand, mode, word(operand));
+    let write = |operand: usize, mode| _create_write_addressing_mode(operand, mode, word(operand));
+    let command = match op_vec.0 {
+        1 => Command::Add(read(1, op_vec.1)?, read(2, op_vec.2)?, write(3, op_vec.3)?),
+        2 => Command::Multiply(read(1, op_vec.1)?, read(2, op_vec.2)?, write(3, op_vec.3)?),
+        3 => Command::IoRead(write(1, op_vec.1)?),
+        4 => Command::IoWrite(read(1, op_vec.1)?),
+        5 => Command::JmpIfTrue(read(1, op_vec.1)?, read(2, op_vec.2)?),
+        6 => Command::JmpIfFalse(read(1, op_vec.1)?, read(2, op_vec.2)?),
+        7 => Command::LessThan(read(1, op_vec.1)?, read(2, op_vec.2)?, write(3, op_vec.3)?),
+        8 => Command::Equal(read(1, op_vec.1)?, read(2, op_vec.2)?, write(3, op_vec.3)?),
+        9 => Command::AdjustRelativeBase(read(1, op_vec.1)?),
+        99 => Command::End(),
+        opcode => return Err(MachineErrorKind::UnknownOpcode(opcode)),
+    };
+    Ok((command, command.length()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_modes() {
+        let (command, length) = decode(&[21001, 4, -3, 7]).expect("failed to decode");
+        assert_eq!(
+            command,
+            Command::Add(
+                AddressingMode::Register(4),
+                AddressingMode::Immediate(-3),
+                AddressingMode::Relative(7)
+            )
+        );
+        assert_eq!(length, 4);
+        assert_eq!(format!("{}", command), "add [4], #-3, rb+7");
+        assert_eq!(command.encode(), vec![21001, 4, -3, 7]);
+    }
+
+    #[test]
+    fn decode_short_slice() {
+        assert_eq!(decode(&[99]), Ok((Command::End(), 1)));
+        assert_eq!(
+            decode(&[204]),
+            Ok((Command::IoWrite(AddressingMode::Relative(0)), 2))
+        );
+        assert_eq!(format!("{}", decode(&[204, -1]).unwrap().0), "out rb-1");
+    }
+}
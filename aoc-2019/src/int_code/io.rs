@@ -0,0 +1,164 @@
+use super::ascii::is_ascii;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{Receiver, Sender};
+
+// A source of values for the machine's input instruction. `None` means no value is available,
+// either because the source is exhausted or because it has closed.
+pub trait Input<W = i64> {
+    fn read(&mut self) -> Option<W>;
+}
+
+// A sink for the machine's output instruction. Returns false once the sink can no longer
+// accept values.
+pub trait Output<W = i64> {
+    fn write(&mut self, value: W) -> bool;
+}
+
+impl<W> Input<W> for Receiver<W> {
+    fn read(&mut self) -> Option<W> {
+        self.recv().ok()
+    }
+}
+
+impl<W> Output<W> for Sender<W> {
+    fn write(&mut self, value: W) -> bool {
+        self.send(value).is_ok()
+    }
+}
+
+impl<W> Input<W> for VecDeque<W> {
+    fn read(&mut self) -> Option<W> {
+        self.pop_front()
+    }
+}
+
+impl<W> Output<W> for VecDeque<W> {
+    fn write(&mut self, value: W) -> bool {
+        self.push_back(value);
+        true
+    }
+}
+
+// Reads from the front, so each read moves the rest of the vector along. Fine for the few
+// values most puzzles take, but long input streams should use a `VecDeque`.
+impl<W> Input<W> for Vec<W> {
+    fn read(&mut self) -> Option<W> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+}
+
+impl<W> Output<W> for Vec<W> {
+    fn write(&mut self, value: W) -> bool {
+        self.push(value);
+        true
+    }
+}
+
+impl<W, F: FnMut() -> Option<W>> Input<W> for F {
+    fn read(&mut self) -> Option<W> {
+        self()
+    }
+}
+
+impl<W, F: FnMut(W) -> bool> Output<W> for F {
+    fn write(&mut self, value: W) -> bool {
+        self(value)
+    }
+}
+
+// Reads lines from stdin and feeds them to the machine one character code at a time,
+// including the trailing newline.
+#[derive(Debug, Default)]
+pub struct AsciiStdin {
+    buffer: VecDeque<i64>,
+}
+
+impl AsciiStdin {
+    pub fn new() -> Self {
+        AsciiStdin {
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+impl Input for AsciiStdin {
+    fn read(&mut self) -> Option<i64> {
+        if self.buffer.is_empty() {
+            let mut line = String::new();
+            match io::stdin().lock().read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => self.buffer.extend(line.bytes().map(i64::from)),
+            }
+        }
+        self.buffer.pop_front()
+    }
+}
+
+// Prints ASCII values as characters and anything outside the ASCII range as a number on its
+// own line.
+#[derive(Debug, Default)]
+pub struct AsciiStdout;
+
+impl Output for AsciiStdout {
+    fn write(&mut self, value: i64) -> bool {
+        let mut stdout = io::stdout();
+        let result = if is_ascii(value) {
+            write!(stdout, "{}", value as u8 as char)
+        } else {
+            writeln!(stdout, "{}", value)
+        };
+        result.and_then(|_| stdout.flush()).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn vec_input_in_order() {
+        let mut input = vec![1, 2];
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(2));
+        assert_eq!(input.read(), None);
+    }
+
+    #[test]
+    fn closure_io() {
+        let mut count = 0;
+        let mut input = || {
+            count += 1;
+            Some(count)
+        };
+        assert_eq!(input.read(), Some(1));
+        assert_eq!(input.read(), Some(2));
+
+        let mut seen = Vec::new();
+        let mut output = |value| {
+            seen.push(value);
+            value < 10
+        };
+        assert!(output.write(3));
+        assert!(!output.write(10));
+        assert_eq!(seen, vec![3, 10]);
+    }
+
+    #[test]
+    fn channel_closed() {
+        let (input_tx, mut input_rx) = mpsc::channel();
+        input_tx.send(4).expect("failed to send data");
+        drop(input_tx);
+        assert_eq!(input_rx.read(), Some(4));
+        assert_eq!(input_rx.read(), None);
+
+        let (mut output_tx, output_rx) = mpsc::channel();
+        drop(output_rx);
+        assert!(!output_tx.write(1));
+    }
+}
@@ -0,0 +1,279 @@
+use std::fs;
+use std::io::{self, Read};
+
+// Loads Intcode programs from either of two encodings.
+//
+// The text encoding is the puzzle input format, made forgiving: values are separated by
+// commas, any whitespace (including newlines) may appear around them, a trailing comma is
+// allowed and `#` starts a comment that runs to the end of the line.
+//
+//     # day 2 example
+//     1, 9, 10, 3,
+//     2, 3, 11, 0,
+//     99, 30, 40, 50
+//
+// The compact encoding is `MAGIC` followed by each value as a zigzag LEB128 varint, so small
+// values of either sign take a single byte.
+pub static MAGIC: &[u8] = b"\0icb1";
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    // `line` and `column` are 1-based and point at the start of `token`, which is empty when a
+    // value is missing between two commas.
+    BadValue {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    // The compact encoding ended part way through the value starting at byte `offset` of the
+    // values after `MAGIC`, or the value didn't fit in 64 bits.
+    BadVarint {
+        offset: usize,
+    },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::BadValue {
+                line,
+                column,
+                token,
+            } if token.is_empty() => {
+                write!(f, "line {}, column {}: missing value", line, column)
+            }
+            LoadError::BadValue {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "line {}, column {}: '{}' is not a number",
+                line, column, token
+            ),
+            LoadError::BadVarint { offset } => {
+                write!(f, "payload byte {}: truncated or oversized value", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+// Callers that only deal in io::Result see parse failures as invalid data.
+impl From<LoadError> for io::Error {
+    fn from(error: LoadError) -> Self {
+        match error {
+            LoadError::Io(error) => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+// A value between two commas, and where it starts.
+struct Field {
+    token: String,
+    line: usize,
+    column: usize,
+}
+
+impl Field {
+    fn parse(self) -> Result<i64, LoadError> {
+        self.token.parse().map_err(|_| LoadError::BadValue {
+            line: self.line,
+            column: self.column,
+            token: self.token,
+        })
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<i64>, LoadError> {
+    let mut fields = Vec::new();
+    let mut field = Field {
+        token: String::new(),
+        line: 1,
+        column: 1,
+    };
+    for (number, line) in source.lines().enumerate() {
+        let code = line.split('#').next().unwrap_or("");
+        for (column, c) in code.chars().enumerate() {
+            if c == ',' {
+                field.token.truncate(field.token.trim_end().len());
+                let next = Field {
+                    token: String::new(),
+                    line: number + 1,
+                    column: column + 2,
+                };
+                fields.push(std::mem::replace(&mut field, next));
+            } else if !c.is_whitespace() || !field.token.is_empty() {
+                if field.token.is_empty() {
+                    field.line = number + 1;
+                    field.column = column + 1;
+                }
+                field.token.push(c);
+            }
+        }
+        // whitespace inside a value makes it invalid, but not whitespace after it.
+        field.token.truncate(field.token.trim_end().len());
+    }
+    // the last value may be left out, which allows a trailing comma or an empty program.
+    if !field.token.is_empty() {
+        fields.push(field);
+    }
+    fields.into_iter().map(Field::parse).collect()
+}
+
+pub fn encode_compact(program: &[i64]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    for value in program {
+        let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        loop {
+            let byte = (zigzag & 0x7f) as u8;
+            zigzag >>= 7;
+            if zigzag == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    bytes
+}
+
+// Decodes the values after `MAGIC`.
+pub fn decode_compact(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    let mut program = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let start = offset;
+        let mut zigzag: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = match bytes.get(offset) {
+                // the tenth byte only has the top bit left to give, and must end the value.
+                Some(byte) if shift < 63 || *byte <= 1 => *byte,
+                _ => return Err(LoadError::BadVarint { offset: start }),
+            };
+            offset += 1;
+            zigzag |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        program.push((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+    }
+    Ok(program)
+}
+
+// Reads a program in either encoding, telling them apart by `MAGIC`.
+pub fn read<R: Read>(mut reader: R) -> Result<Vec<i64>, LoadError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    match bytes.strip_prefix(MAGIC) {
+        Some(compact) => decode_compact(compact),
+        None => {
+            let source = String::from_utf8(bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            parse(&source)
+        }
+    }
+}
+
+// Loads a program from a file, or from stdin if `path` is `-`.
+pub fn load(path: &str) -> Result<Vec<i64>, LoadError> {
+    match path {
+        "-" => read(io::stdin().lock()),
+        _ => read(fs::File::open(path)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgiving_text() {
+        let source = "# day 2 example\n1, 9, 10, 3,\r\n  2,3,11,0, # halts next\n99,30,40,50\n";
+        assert_eq!(
+            parse(source).expect("failed to parse"),
+            vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+        assert_eq!(parse("1,2,\n").expect("failed to parse"), vec![1, 2]);
+        assert_eq!(parse("").expect("failed to parse"), vec![]);
+    }
+
+    #[test]
+    fn error_positions() {
+        let position = |source| match parse(source) {
+            Err(LoadError::BadValue {
+                line,
+                column,
+                token,
+            }) => (line, column, token),
+            other => panic!("expected a bad value, found {:?}", other),
+        };
+        assert_eq!(position("1,2,\n3, x4 ,5"), (2, 4, String::from("x4")));
+        assert_eq!(position("1,,2"), (1, 3, String::new()));
+        assert_eq!(position("1,\n,2"), (1, 3, String::new()));
+        assert_eq!(position("1, 2 3"), (1, 4, String::from("2 3")));
+        let error = parse("1,\n  -").expect_err("parsed a bad value");
+        assert_eq!(error.to_string(), "line 2, column 3: '-' is not a number");
+    }
+
+    #[test]
+    fn compact_encoding() {
+        let program = vec![0, 1, -1, 63, -64, 64, 1 << 40, i64::MAX, i64::MIN, 99];
+        let bytes = encode_compact(&program);
+        assert_eq!(bytes[MAGIC.len()..MAGIC.len() + 5], [0, 2, 1, 126, 127]);
+        assert_eq!(read(&bytes[..]).expect("failed to read"), program);
+        assert_eq!(
+            read(&b"1,2,99\n"[..]).expect("failed to read"),
+            vec![1, 2, 99]
+        );
+        // 99 takes two bytes, so dropping one leaves it unfinished.
+        match read(&bytes[..bytes.len() - 1]) {
+            Err(error @ LoadError::BadVarint { .. }) => assert_eq!(
+                error.to_string(),
+                format!(
+                    "payload byte {}: truncated or oversized value",
+                    bytes.len() - 2 - MAGIC.len()
+                )
+            ),
+            other => panic!("expected a truncated value, found {:?}", other),
+        }
+        match decode_compact(&[2, 0x80]) {
+            Err(LoadError::BadVarint { offset: 1 }) => {}
+            other => panic!("expected a truncated value, found {:?}", other),
+        }
+        assert!(decode_compact(&[0xff; 11]).is_err());
+        let mut longest = vec![0xff; 9];
+        longest.push(1);
+        assert_eq!(
+            decode_compact(&longest).expect("failed to decode"),
+            vec![i64::MIN]
+        );
+        for last in [2, 0x81] {
+            longest[9] = last;
+            match decode_compact(&longest) {
+                Err(LoadError::BadVarint { offset: 0 }) => {}
+                other => panic!("expected an oversized value, found {:?}", other),
+            }
+        }
+    }
+}
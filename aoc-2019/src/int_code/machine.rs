@@ -1,38 +1,94 @@
-use std::sync::mpsc::{Receiver, Sender};
+use super::instruction::{decode, AddressingMode, Command};
+use super::io::{Input, Output};
+use super::memory::{Memory, OutOfBounds};
+use super::profile::Profile;
+use super::snapshot::Snapshot;
+use super::trace::{TraceEvent, Tracer};
+use super::word::{clamp, Word};
+use std::collections::VecDeque;
 
-static MEMORY_SIZE: usize = 4096;
+type MachineMemoryType = i64;
 
-#[derive(Copy, Clone, Debug)]
-enum AddressingMode {
-    Register(usize),
-    Immediate(i64),
-    Relative(i64),
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExecutionState<W = MachineMemoryType> {
+    Running,
+    NeedsInput,
+    Output(W),
+    Halted,
 }
 
-#[derive(Copy, Clone, Debug)]
-enum Command {
-    End(),
-    Add(AddressingMode, AddressingMode, AddressingMode),
-    Multiply(AddressingMode, AddressingMode, AddressingMode),
-    JmpIfTrue(AddressingMode, AddressingMode),
-    JmpIfFalse(AddressingMode, AddressingMode),
-    LessThan(AddressingMode, AddressingMode, AddressingMode),
-    Equal(AddressingMode, AddressingMode, AddressingMode),
-    IoRead(AddressingMode),
-    IoWrite(AddressingMode),
-    AdjustRelativeBase(AddressingMode),
+// Operands are numbered from 1, matching their position after the instruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MachineErrorKind {
+    UnknownOpcode(MachineMemoryType),
+    InvalidAddressingMode {
+        operand: usize,
+        mode: MachineMemoryType,
+    },
+    WriteToImmediate {
+        operand: usize,
+    },
+    NegativeAddress(MachineMemoryType),
+    AddressOutOfBounds {
+        address: usize,
+        limit: usize,
+    },
+    InputClosed,
+    OutputClosed,
+    // An add or multiply result, or the relative base, doesn't fit in the machine's words.
+    Overflow,
 }
 
-type MachineMemoryType = i64;
+impl std::fmt::Display for MachineErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MachineErrorKind::UnknownOpcode(opcode) => write!(f, "unknown opcode {}", opcode),
+            MachineErrorKind::InvalidAddressingMode { operand, mode } => write!(
+                f,
+                "unrecognised addressing mode {} for operand {}",
+                mode, operand
+            ),
+            MachineErrorKind::WriteToImmediate { operand } => {
+                write!(f, "operand {} is written to but is immediate", operand)
+            }
+            MachineErrorKind::NegativeAddress(address) => {
+                write!(f, "negative address {}", address)
+            }
+            MachineErrorKind::AddressOutOfBounds { address, limit } => write!(
+                f,
+                "address {} is beyond the memory limit {}",
+                address, limit
+            ),
+            MachineErrorKind::InputClosed => write!(f, "input closed before machine finished"),
+            MachineErrorKind::OutputClosed => write!(f, "output closed before machine finished"),
+            MachineErrorKind::Overflow => write!(f, "arithmetic overflow"),
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+impl From<OutOfBounds> for MachineErrorKind {
+    fn from(error: OutOfBounds) -> Self {
+        MachineErrorKind::AddressOutOfBounds {
+            address: error.address,
+            limit: error.limit,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct MachineError {
-    reason: String,
+    pub program_counter: usize,
+    pub instruction: MachineMemoryType,
+    pub kind: MachineErrorKind,
 }
 
 impl std::fmt::Display for MachineError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "machine failed error msg: {}", self.reason)
+        write!(
+            f,
+            "machine failed at {} (instruction {}): {}",
+            self.program_counter, self.instruction, self.kind
+        )
     }
 }
 
@@ -42,262 +98,450 @@ impl std::error::Error for MachineError {
     }
 }
 
+// Holds the machine's tracer so that `Machine` can still derive `Debug`.
+struct TraceHook(Box<dyn Tracer + Send>);
+
+impl std::fmt::Debug for TraceHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "TraceHook")
+    }
+}
+
 #[derive(Debug)]
-pub struct Machine {
-    state: Vec<MachineMemoryType>,
+pub struct Machine<I, O, W = MachineMemoryType> {
+    state: Memory<W>,
+    program_counter: usize,
     relative_base: i64,
-    input: Receiver<MachineMemoryType>,
-    output: Sender<MachineMemoryType>,
+    input: I,
+    output: O,
+    tracer: Option<TraceHook>,
+    last_write: Option<(usize, W)>,
+    decode_cache: Option<Vec<Option<(Command, usize)>>>,
+    profile: Profile,
 }
 
-impl Machine {
-    pub fn new(
-        mut program: Vec<MachineMemoryType>,
-        input: Receiver<MachineMemoryType>,
-        output: Sender<MachineMemoryType>,
-    ) -> Self {
-        program.append(&mut vec![0; MEMORY_SIZE - program.len()]);
+// Forks the machine, including its I/O. The tracer is not shared with the copy.
+impl<I: Clone, O: Clone, W: Word> Clone for Machine<I, O, W> {
+    fn clone(&self) -> Self {
         Machine {
-            state: program,
+            state: self.state.clone(),
+            program_counter: self.program_counter,
+            relative_base: self.relative_base,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            tracer: None,
+            last_write: self.last_write.clone(),
+            decode_cache: self.decode_cache.clone(),
+            profile: self.profile,
+        }
+    }
+}
+
+impl Machine<VecDeque<MachineMemoryType>, Vec<MachineMemoryType>> {
+    // A machine with in-memory I/O, driven with `push_input` and `step`/`run_until_io`.
+    pub fn with_program(program: Vec<MachineMemoryType>) -> Self {
+        Machine::new(program, VecDeque::new(), Vec::new())
+    }
+}
+
+impl<O: Output<W>, W: Word> Machine<VecDeque<W>, O, W> {
+    pub fn push_input(&mut self, value: W) {
+        self.input.push_back(value);
+    }
+}
+
+impl<I: Input, O: Output> Machine<I, O> {
+    pub fn new(program: Vec<MachineMemoryType>, input: I, output: O) -> Self {
+        Machine::from_words(program, input, output)
+    }
+
+    pub fn from_snapshot(snapshot: Snapshot, input: I, output: O) -> Self {
+        let mut machine = Machine::new(Vec::new(), input, output);
+        machine.restore(snapshot);
+        machine
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.state.as_vec().clone(),
+            memory_limit: self.state.limit(),
+            program_counter: self.program_counter,
+            relative_base: self.relative_base,
+        }
+    }
+
+    // Replaces memory and registers with the snapshot's, leaving I/O untouched.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.state = Memory::new(snapshot.memory);
+        self.state.set_limit(snapshot.memory_limit);
+        self.program_counter = snapshot.program_counter;
+        self.relative_base = snapshot.relative_base;
+        self.last_write = None;
+        if let Some(cache) = &mut self.decode_cache {
+            cache.clear();
+        }
+    }
+}
+
+impl<I: Input<W>, O: Output<W>, W: Word> Machine<I, O, W> {
+    // A machine with a word type other than i64, see `word`.
+    pub fn from_words(program: Vec<W>, input: I, output: O) -> Self {
+        Machine {
+            state: Memory::new(program),
+            program_counter: 0,
             relative_base: 0,
             input,
             output,
+            tracer: None,
+            last_write: None,
+            decode_cache: Some(Vec::new()),
+            profile: Profile::default(),
         }
     }
 
-    fn _generate_operation_vec(
-        &self,
-        instruction: MachineMemoryType,
-    ) -> Option<(MachineMemoryType, usize, usize, usize)> {
-        let opcode = instruction % 100;
-        let read_mode_1 = (instruction / 100) % 10;
-        let read_mode_2 = (instruction / 1000) % 10;
-        let read_mode_3 = (instruction / 10000) % 10;
-        Some((
-            opcode,
-            read_mode_1 as usize,
-            read_mode_2 as usize,
-            read_mode_3 as usize,
-        ))
-    }
-
-    fn _create_addressing_mode(mode: usize, value: MachineMemoryType) -> AddressingMode {
-        match mode {
-            0 => AddressingMode::Register(value as usize),
-            1 => AddressingMode::Immediate(value),
-            2 => AddressingMode::Relative(value),
-            _ => panic!("unrecognised memory mode {}", mode),
-        }
+    // Decoded instructions are cached by address and dropped when any of their cells are
+    // written, so self-modifying code sees its changes. Disabling the cache decodes every
+    // instruction as it is executed.
+    pub fn with_decode_cache(mut self, enabled: bool) -> Self {
+        self.decode_cache = if enabled { Some(Vec::new()) } else { None };
+        self
     }
 
-    fn _parse_slice(&self, slice: &[MachineMemoryType]) -> Option<(Command, usize)> {
-        let op_vec = self._generate_operation_vec(slice[0])?;
-        match op_vec.0 {
-            1 => Some((
-                Command::Add(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                    Self::_create_addressing_mode(op_vec.3, slice[3]),
-                ),
-                4,
-            )),
-            2 => Some((
-                Command::Multiply(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                    Self::_create_addressing_mode(op_vec.3, slice[3]),
-                ),
-                4,
-            )),
-            3 => Some((
-                Command::IoRead(Self::_create_addressing_mode(op_vec.1, slice[1])),
-                2,
-            )),
-            4 => Some((
-                Command::IoWrite(Self::_create_addressing_mode(op_vec.1, slice[1])),
-                2,
-            )),
-            5 => Some((
-                Command::JmpIfTrue(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                ),
-                3,
-            )),
-            6 => Some((
-                Command::JmpIfFalse(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                ),
-                3,
-            )),
-            7 => Some((
-                Command::LessThan(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                    Self::_create_addressing_mode(op_vec.3, slice[3]),
-                ),
-                4,
-            )),
-            8 => Some((
-                Command::Equal(
-                    Self::_create_addressing_mode(op_vec.1, slice[1]),
-                    Self::_create_addressing_mode(op_vec.2, slice[2]),
-                    Self::_create_addressing_mode(op_vec.3, slice[3]),
-                ),
-                4,
-            )),
-            9 => Some((
-                Command::AdjustRelativeBase(Self::_create_addressing_mode(op_vec.1, slice[1])),
-                2,
-            )),
-            99 => Some((Command::End(), 1)),
-            _ => None,
+    // Reports every executed instruction to `tracer`.
+    pub fn with_tracer(mut self, tracer: impl Tracer + Send + 'static) -> Self {
+        self.tracer = Some(TraceHook(Box::new(tracer)));
+        self
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Box<dyn Tracer + Send>> {
+        self.tracer.take().map(|hook| hook.0)
+    }
+
+    // Restricts the machine to the instructions of an earlier puzzle. Profiles that predate
+    // growable memory also cap memory at the current program length.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        if !profile.grows_memory() {
+            let limit = std::cmp::min(self.state.limit(), self.state.len());
+            self.state.set_limit(limit);
+        }
+        if let Some(cache) = &mut self.decode_cache {
+            cache.clear();
         }
+        self
+    }
+
+    // Caps how far memory may grow; accesses beyond it fail with a `MachineError`.
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.state.set_limit(limit);
+        self
     }
 
-    fn _read_memory(&self, addressing_mode: AddressingMode) -> MachineMemoryType {
+    fn _address(&self, addressing_mode: AddressingMode) -> Result<usize, MachineErrorKind> {
         match addressing_mode {
-            AddressingMode::Register(pos) => self.state[pos],
-            AddressingMode::Immediate(value) => value,
-            AddressingMode::Relative(offset) => self.state[(self.relative_base + offset) as usize],
+            AddressingMode::Register(pos) => Ok(pos),
+            AddressingMode::Immediate(_) => unreachable!("immediate operand has no address."),
+            AddressingMode::Relative(offset) => match self.relative_base.checked_add(offset) {
+                None => Err(MachineErrorKind::Overflow),
+                Some(address) if address < 0 => Err(MachineErrorKind::NegativeAddress(address)),
+                Some(address) => Ok(address as usize),
+            },
         }
     }
 
-    fn _write_memory(&mut self, addressing_mode: AddressingMode, value: MachineMemoryType) {
+    // Immediate operands are read from the instruction in memory rather than the decoded
+    // command, since a word may not fit in an i64. `operand` is the operand's position.
+    fn _read_memory(
+        &self,
+        operand: usize,
+        addressing_mode: AddressingMode,
+    ) -> Result<W, MachineErrorKind> {
         match addressing_mode {
-            AddressingMode::Register(pos) => self.state[pos] = value,
-            AddressingMode::Immediate(_) => panic!("can't write value."),
-            AddressingMode::Relative(offset) => {
-                self.state[(self.relative_base + offset) as usize] = value
-            }
+            AddressingMode::Immediate(_) => Ok(self.state.get(self.program_counter + operand)),
+            _ => Ok(self.state.read(self._address(addressing_mode)?)?),
         }
     }
 
-    fn _read_input(&mut self, addressing_mode: AddressingMode) {
-        // println!("read: {:?} {}", addressing_mode, self.relative_base);
-        match self.input.recv() {
-            Ok(input) => self._write_memory(addressing_mode, input),
-            Err(e) => panic!("input closed before machine finished {}", e),
-        }
+    fn _write_memory(
+        &mut self,
+        addressing_mode: AddressingMode,
+        value: W,
+    ) -> Result<(), MachineErrorKind> {
+        let address = self._address(addressing_mode)?;
+        self._store(address, value.clone())?;
+        self.last_write = Some((address, value));
+        Ok(())
     }
 
-    fn _write_output(&mut self, addressing_mode: AddressingMode) {
-        // println!("write: {:?} {}", addressing_mode, self.relative_base);
-        match self.output.send(self._read_memory(addressing_mode)) {
-            Ok(()) => {}
-            Err(e) => panic!("failed to send data: {}", e),
+    fn _store(&mut self, address: usize, value: W) -> Result<(), OutOfBounds> {
+        self.state.write(address, value)?;
+        if let Some(cache) = &mut self.decode_cache {
+            // an instruction is at most 4 cells long, so only those starting in the 3 cells
+            // before the write can cover it.
+            let end = std::cmp::min(address + 1, cache.len());
+            for entry in &mut cache[std::cmp::min(address.saturating_sub(3), end)..end] {
+                *entry = None;
+            }
         }
+        Ok(())
     }
 
     fn _two_arg_test(
         &self,
         arg1_mode: AddressingMode,
         arg2_mode: AddressingMode,
-        test: impl Fn(MachineMemoryType, MachineMemoryType) -> bool,
-    ) -> bool {
-        let arg1 = self._read_memory(arg1_mode);
-        let arg2 = self._read_memory(arg2_mode);
-        test(arg1, arg2)
+        test: impl Fn(&W, &W) -> bool,
+    ) -> Result<W, MachineErrorKind> {
+        let arg1 = self._read_memory(1, arg1_mode)?;
+        let arg2 = self._read_memory(2, arg2_mode)?;
+        Ok(W::from_i64(test(&arg1, &arg2) as i64))
     }
 
-    pub fn execute(&mut self) -> Result<(), MachineError> {
-        let mut program_counter = 0;
-        let mut parsed_command =
-            self._parse_slice(&self.state[program_counter..program_counter + 4]);
-        while let Some(command) = parsed_command {
-            match command.0 {
-                Command::End() => {
-                    return Ok(());
-                }
-                Command::Add(v1, v2, res) => {
-                    self._write_memory(res, self._read_memory(v1) + self._read_memory(v2));
-                    program_counter += command.1;
-                }
-                Command::Multiply(v1, v2, res) => {
-                    self._write_memory(res, self._read_memory(v1) * self._read_memory(v2));
-                    program_counter += command.1;
-                }
-                Command::LessThan(arg1, arg2, res) => {
-                    self._write_memory(
-                        res,
-                        self._two_arg_test(arg1, arg2, |v1, v2| -> bool { v1 < v2 }) as i64,
-                    );
-                    program_counter += command.1;
-                }
-                Command::Equal(arg1, arg2, res) => {
-                    self._write_memory(
-                        res,
-                        self._two_arg_test(arg1, arg2, |v1, v2| -> bool { v1 == v2 }) as i64,
-                    );
-                    program_counter += command.1;
-                }
-                Command::IoRead(pos) => {
-                    self._read_input(pos);
-                    program_counter += command.1;
+    fn _arithmetic(
+        &self,
+        arg1_mode: AddressingMode,
+        arg2_mode: AddressingMode,
+        operation: impl Fn(&W, &W) -> Option<W>,
+    ) -> Result<W, MachineErrorKind> {
+        let arg1 = self._read_memory(1, arg1_mode)?;
+        let arg2 = self._read_memory(2, arg2_mode)?;
+        operation(&arg1, &arg2).ok_or(MachineErrorKind::Overflow)
+    }
+
+    fn _jump_target(&self, ptr: AddressingMode) -> Result<usize, MachineErrorKind> {
+        match clamp(&self._read_memory(2, ptr)?) {
+            target if target < 0 => Err(MachineErrorKind::NegativeAddress(target)),
+            target => Ok(target as usize),
+        }
+    }
+
+    fn _decode(&mut self, address: usize) -> Result<(Command, usize), MachineErrorKind> {
+        if let Some(Some(Some(decoded))) = self.decode_cache.as_ref().map(|c| c.get(address)) {
+            return Ok(*decoded);
+        }
+        let instruction = self.state.get(address);
+        if instruction.to_i64().is_none() {
+            return Err(MachineErrorKind::UnknownOpcode(clamp(&instruction)));
+        }
+        // operands too big for an i64 become out of range addresses, or for immediates are
+        // read again by `_read_memory`.
+        let slice = [
+            clamp(&instruction),
+            clamp(&self.state.get(address + 1)),
+            clamp(&self.state.get(address + 2)),
+            clamp(&self.state.get(address + 3)),
+        ];
+        let decoded = decode(&slice)?;
+        self.profile.check(&decoded.0)?;
+        if let Some(cache) = &mut self.decode_cache {
+            if address < self.state.len() {
+                if cache.len() < self.state.len() {
+                    cache.resize(self.state.len(), None);
                 }
-                Command::IoWrite(pos) => {
-                    self._write_output(pos);
-                    program_counter += command.1;
+                cache[address] = Some(decoded);
+            }
+        }
+        Ok(decoded)
+    }
+
+    // Operand values for tracing, read without failing on bad addresses.
+    fn _trace_values(&self, command: &Command) -> Vec<MachineMemoryType> {
+        let operands = command.operands();
+        let target = command.target().map(|_| operands.len() - 1);
+        operands
+            .into_iter()
+            .enumerate()
+            .map(|(i, operand)| match operand {
+                AddressingMode::Immediate(value) => value,
+                _ => match self._address(operand) {
+                    Ok(address) if Some(i) == target => address as MachineMemoryType,
+                    Ok(address) => clamp(&self.state.get(address)),
+                    Err(_) => 0,
+                },
+            })
+            .collect()
+    }
+
+    // An error of `kind` at the current instruction, for callers driving the machine with
+    // `step` that need to stop it the way `execute` would, such as on `InputClosed`.
+    pub fn error(&self, kind: MachineErrorKind) -> MachineError {
+        MachineError {
+            program_counter: self.program_counter,
+            instruction: clamp(&self.state.get(self.program_counter)),
+            kind,
+        }
+    }
+
+    // Executes a single instruction. An input instruction with no value available leaves the
+    // program counter where it is and reports `NeedsInput`, so it is retried on the next step.
+    // Outputs are written to the output and also reported as `Output`. A failed instruction
+    // leaves the machine unchanged.
+    pub fn step(&mut self) -> Result<ExecutionState<W>, MachineError> {
+        self._step().map_err(|kind| self.error(kind))
+    }
+
+    fn _step(&mut self) -> Result<ExecutionState<W>, MachineErrorKind> {
+        let program_counter = self.program_counter;
+        let (command, length) = self._decode(program_counter)?;
+        let values = match self.tracer {
+            Some(_) => self._trace_values(&command),
+            None => Vec::new(),
+        };
+        let relative_base = self.relative_base;
+        self.last_write = None;
+        let mut next_counter = program_counter + length;
+        let mut state = ExecutionState::Running;
+        match command {
+            Command::End() => {
+                next_counter = program_counter;
+                state = ExecutionState::Halted;
+            }
+            Command::Add(v1, v2, res) => {
+                let result = self._arithmetic(v1, v2, W::try_add)?;
+                self._write_memory(res, result)?;
+            }
+            Command::Multiply(v1, v2, res) => {
+                let result = self._arithmetic(v1, v2, W::try_mul)?;
+                self._write_memory(res, result)?;
+            }
+            Command::LessThan(arg1, arg2, res) => {
+                let result = self._two_arg_test(arg1, arg2, |v1, v2| -> bool { v1 < v2 })?;
+                self._write_memory(res, result)?;
+            }
+            Command::Equal(arg1, arg2, res) => {
+                let result = self._two_arg_test(arg1, arg2, |v1, v2| -> bool { v1 == v2 })?;
+                self._write_memory(res, result)?;
+            }
+            Command::IoRead(pos) => {
+                // check the address first so a bad operand doesn't consume input.
+                self.state.check(self._address(pos)?)?;
+                match self.input.read() {
+                    Some(input) => self._write_memory(pos, input)?,
+                    None => return Ok(ExecutionState::NeedsInput),
                 }
-                Command::JmpIfTrue(test, ptr) => {
-                    if self._read_memory(test) != 0 {
-                        program_counter = self._read_memory(ptr) as usize
-                    } else {
-                        program_counter += command.1;
-                    }
+            }
+            Command::IoWrite(pos) => {
+                let value = self._read_memory(1, pos)?;
+                if !self.output.write(value.clone()) {
+                    return Err(MachineErrorKind::OutputClosed);
                 }
-                Command::JmpIfFalse(test, ptr) => {
-                    if self._read_memory(test) == 0 {
-                        program_counter = self._read_memory(ptr) as usize
-                    } else {
-                        program_counter += command.1;
-                    }
+                state = ExecutionState::Output(value);
+            }
+            Command::JmpIfTrue(test, ptr) => {
+                if self._read_memory(1, test)? != W::default() {
+                    next_counter = self._jump_target(ptr)?;
                 }
-                Command::AdjustRelativeBase(amount_address) => {
-                    let amount = self._read_memory(amount_address);
-                    self.relative_base += amount;
-                    program_counter += command.1;
+            }
+            Command::JmpIfFalse(test, ptr) => {
+                if self._read_memory(1, test)? == W::default() {
+                    next_counter = self._jump_target(ptr)?;
                 }
             }
-            parsed_command = self._parse_slice(
-                &self.state[program_counter..std::cmp::min(program_counter + 4, self.state.len())],
-            );
+            Command::AdjustRelativeBase(amount_address) => {
+                let amount = self._read_memory(1, amount_address)?;
+                self.relative_base = amount
+                    .to_i64()
+                    .and_then(|amount| self.relative_base.checked_add(amount))
+                    .ok_or(MachineErrorKind::Overflow)?;
+            }
+        }
+        self.program_counter = next_counter;
+        if let Some(TraceHook(tracer)) = &mut self.tracer {
+            tracer.trace(&TraceEvent {
+                program_counter,
+                relative_base,
+                command,
+                values,
+                write: self
+                    .last_write
+                    .as_ref()
+                    .map(|(address, value)| (*address, clamp(value))),
+            });
         }
-        Err(MachineError {
-            reason: String::from("ran out of instructions."),
-        })
+        Ok(state)
     }
 
-    pub fn read_memory(&self) -> &Vec<MachineMemoryType> {
-        &self.state
+    // Steps until the machine halts, produces an output or is waiting on input.
+    pub fn run_until_io(&mut self) -> Result<ExecutionState<W>, MachineError> {
+        loop {
+            match self.step()? {
+                ExecutionState::Running => {}
+                state => return Ok(state),
+            }
+        }
+    }
+
+    pub fn execute(&mut self) -> Result<(), MachineError> {
+        loop {
+            match self.run_until_io()? {
+                ExecutionState::Halted => return Ok(()),
+                ExecutionState::NeedsInput => return Err(self.error(MachineErrorKind::InputClosed)),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn read_memory(&self) -> &Vec<W> {
+        self.state.as_vec()
+    }
+
+    // Reads any cell, including those past the end of memory which read as 0.
+    pub fn read_address(&self, address: usize) -> W {
+        self.state.get(address)
+    }
+
+    // Patches a cell, for example to set a program's inputs before it runs. Instructions
+    // already decoded from the cell are dropped, as for writes made by the program.
+    pub fn write_address(&mut self, address: usize, value: W) -> Result<(), MachineError> {
+        self._store(address, value)
+            .map_err(|e| self.error(MachineErrorKind::from(e)))
     }
 
     pub fn read_relative_base(&self) -> MachineMemoryType {
         self.relative_base
     }
+
+    pub fn read_program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    // The address and value stored by the last instruction, if it stored anything.
+    pub fn read_last_write(&self) -> Option<(usize, W)> {
+        self.last_write.clone()
+    }
+
+    pub fn input_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut O {
+        &mut self.output
+    }
+
+    pub fn into_output(self) -> O {
+        self.output
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::assembler::assemble;
+    use super::super::read_fixture;
+    use super::super::word::{words, BigInt};
     use super::*;
-    use std::fs;
-    use std::io::{self};
+    use std::num::Wrapping;
     use std::sync::mpsc;
 
-    fn read_file(path: &str) -> io::Result<Vec<i64>> {
-        Ok(fs::read_to_string(path)?
-            .split(',')
-            .map(|x| x.parse::<i64>().unwrap())
-            .collect())
-    }
-
     #[test]
     fn day2_example_1() {
         let (_, input_rx) = mpsc::channel();
         let (output_tx, _) = mpsc::channel();
         let mut machine = Machine::new(vec![1, 0, 0, 0, 99], input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(machine.read_memory()[0], 2);
     }
 
@@ -317,7 +561,7 @@ mod tests {
             input_rx,
             output_tx,
         );
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(machine.read_memory()[0], 3101844);
     }
 
@@ -326,13 +570,13 @@ mod tests {
         let (_, input_rx) = mpsc::channel();
         let (output_tx, _) = mpsc::channel();
         let mut machine = Machine::new(vec![1002, 4, 3, 4, 33], input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(machine.read_memory()[4], 99);
     }
 
     #[test]
     fn day5_example_1() {
-        for (input, output) in vec![(1, 1), (0, 0)] {
+        for (input, output) in [(1, 1), (0, 0)] {
             let (input_tx, input_rx) = mpsc::channel();
             let (output_tx, output_rx) = mpsc::channel();
             input_tx.send(input).expect("failed to send data");
@@ -341,14 +585,14 @@ mod tests {
                 input_rx,
                 output_tx,
             );
-            machine.execute();
+            machine.execute().expect("failed to execute");
             assert_eq!(output_rx.recv().expect("failed to read output"), output);
         }
     }
 
     #[test]
     fn day5_example_2() {
-        for (input, output) in vec![(7, 999), (8, 1000), (9, 1001)] {
+        for (input, output) in [(7, 999), (8, 1000), (9, 1001)] {
             let (input_tx, input_rx) = mpsc::channel();
             let (output_tx, output_rx) = mpsc::channel();
             input_tx.send(input).expect("failed to send data");
@@ -361,7 +605,7 @@ mod tests {
                 input_rx,
                 output_tx,
             );
-            machine.execute();
+            machine.execute().expect("failed to execute");
             assert_eq!(output_rx.recv().expect("failed to read output"), output);
         }
     }
@@ -371,10 +615,9 @@ mod tests {
         let (input_tx, input_rx) = mpsc::channel();
         let (output_tx, output_rx) = mpsc::channel();
         input_tx.send(5).expect("failed to send data");
-        let program = read_file("/home/tim/projects/AoC19/resources/day5input")
-            .expect("failed to read day 5 in");
+        let program = read_fixture("day5.intcode");
         let mut machine = Machine::new(program, input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(output_rx.recv().expect("failed to read output"), 773660);
     }
 
@@ -387,7 +630,7 @@ mod tests {
             109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
         ];
         let mut machine = Machine::new(program.clone(), input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         let output: Vec<MachineMemoryType> = output_rx.try_iter().collect();
         assert_eq!(program, output);
     }
@@ -402,7 +645,7 @@ mod tests {
             input_rx,
             output_tx,
         );
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(
             format!("{}", output_rx.recv().expect("failed to read output")).len(),
             16
@@ -415,7 +658,7 @@ mod tests {
         let (output_tx, output_rx) = mpsc::channel();
         input_tx.send(5).expect("failed to send data");
         let mut machine = Machine::new(vec![104, 1125899906842624, 99], input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(
             output_rx.recv().expect("failed to read output"),
             1125899906842624
@@ -427,10 +670,9 @@ mod tests {
         let (input_tx, input_rx) = mpsc::channel();
         let (output_tx, output_rx) = mpsc::channel();
         input_tx.send(1).expect("failed to send data");
-        let program = read_file("/home/tim/projects/AoC19/resources/day9input")
-            .expect("failed to read day 9 in");
+        let program = read_fixture("day9.intcode");
         let mut machine = Machine::new(program.clone(), input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(output_rx.try_recv().expect("expect output"), 3906448201);
     }
 
@@ -439,10 +681,385 @@ mod tests {
         let (input_tx, input_rx) = mpsc::channel();
         let (output_tx, output_rx) = mpsc::channel();
         input_tx.send(2).expect("failed to send data");
-        let program = read_file("/home/tim/projects/AoC19/resources/day9input")
-            .expect("failed to read day 9 in");
+        let program = read_fixture("day9.intcode");
         let mut machine = Machine::new(program.clone(), input_rx, output_tx);
-        machine.execute();
+        machine.execute().expect("failed to execute");
         assert_eq!(output_rx.try_recv().expect("expect output"), 59785);
     }
+
+    #[test]
+    fn step_day2_example_1() {
+        let mut machine = Machine::with_program(vec![1, 0, 0, 0, 99]);
+        assert_eq!(
+            machine.step().expect("failed to step"),
+            ExecutionState::Running
+        );
+        assert_eq!(machine.read_program_counter(), 4);
+        assert_eq!(
+            machine.step().expect("failed to step"),
+            ExecutionState::Halted
+        );
+        assert_eq!(
+            machine.step().expect("failed to step"),
+            ExecutionState::Halted
+        );
+        assert_eq!(machine.read_memory()[0], 2);
+    }
+
+    #[test]
+    fn run_until_io_waits_for_input() {
+        let mut machine = Machine::with_program(vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        assert_eq!(
+            machine.run_until_io().expect("failed to run"),
+            ExecutionState::NeedsInput
+        );
+        assert_eq!(machine.read_program_counter(), 0);
+        assert_eq!(
+            machine.run_until_io().expect("failed to run"),
+            ExecutionState::NeedsInput
+        );
+        machine.push_input(41);
+        assert_eq!(
+            machine.run_until_io().expect("failed to run"),
+            ExecutionState::Output(42)
+        );
+        assert_eq!(
+            machine.run_until_io().expect("failed to run"),
+            ExecutionState::Halted
+        );
+    }
+
+    #[test]
+    fn run_until_io_interleaved_machines() {
+        // two copies of the day 5 comparison program driven from one thread.
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        let mut machines = [
+            Machine::with_program(program.clone()),
+            Machine::with_program(program),
+        ];
+        for (machine, input) in machines.iter_mut().zip([7, 9].iter()) {
+            assert_eq!(
+                machine.run_until_io().expect("failed to run"),
+                ExecutionState::NeedsInput
+            );
+            machine.push_input(*input);
+        }
+        let outputs: Vec<ExecutionState> = machines
+            .iter_mut()
+            .map(|machine| machine.run_until_io().expect("failed to run"))
+            .collect();
+        assert_eq!(
+            outputs,
+            vec![ExecutionState::Output(999), ExecutionState::Output(1001)]
+        );
+    }
+
+    #[test]
+    fn execute_without_input() {
+        let mut machine = Machine::with_program(vec![3, 0, 99]);
+        assert!(machine.execute().is_err());
+    }
+
+    #[test]
+    fn vec_io() {
+        let program = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::new(program.clone(), Vec::new(), Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output(), program);
+    }
+
+    #[test]
+    fn closure_io() {
+        let mut machine = Machine::new(
+            vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0],
+            || Some(9),
+            |value| value != 10,
+        );
+        assert!(machine.execute().is_err());
+    }
+
+    #[test]
+    fn program_longer_than_4096() {
+        let mut program = vec![1101, 2, 3, 5000, 99];
+        program.resize(5001, 0);
+        let mut machine = Machine::with_program(program);
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.read_memory()[5000], 5);
+    }
+
+    #[test]
+    fn high_address_write() {
+        let mut machine = Machine::with_program(vec![109, 100000, 21101, 2, 3, 7, 99]);
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.read_memory()[100007], 5);
+    }
+
+    #[test]
+    fn memory_limit() {
+        let mut machine = Machine::with_program(vec![1101, 2, 3, 5000, 99]).with_memory_limit(4096);
+        assert_eq!(
+            machine.execute().expect_err("expected an error").kind,
+            MachineErrorKind::AddressOutOfBounds {
+                address: 5000,
+                limit: 4096
+            }
+        );
+
+        // the value isn't taken from the input when the target is out of bounds.
+        let mut machine = Machine::with_program(vec![3, 5000, 99]).with_memory_limit(4096);
+        machine.push_input(7);
+        assert_eq!(
+            machine.step().expect_err("expected an error").kind,
+            MachineErrorKind::AddressOutOfBounds {
+                address: 5000,
+                limit: 4096
+            }
+        );
+        assert_eq!(machine.input_mut().pop_front(), Some(7));
+    }
+
+    #[test]
+    fn errors() {
+        for (program, kind) in [
+            (vec![4, -1, 99], MachineErrorKind::NegativeAddress(-1)),
+            (
+                vec![109, -5, 204, 1, 99],
+                MachineErrorKind::NegativeAddress(-4),
+            ),
+            (vec![1105, 1, -3, 99], MachineErrorKind::NegativeAddress(-3)),
+            (
+                vec![11101, 1, 1, 0, 99],
+                MachineErrorKind::WriteToImmediate { operand: 3 },
+            ),
+            (
+                vec![103, 0, 99],
+                MachineErrorKind::WriteToImmediate { operand: 1 },
+            ),
+            (
+                vec![3001, 0, 0, 0, 99],
+                MachineErrorKind::InvalidAddressingMode {
+                    operand: 2,
+                    mode: 3,
+                },
+            ),
+            (
+                vec![1101, 20, 22, 4, 0],
+                MachineErrorKind::UnknownOpcode(42),
+            ),
+            (vec![3, 0, 99], MachineErrorKind::InputClosed),
+        ] {
+            let mut machine = Machine::with_program(program);
+            let error = machine.execute().expect_err("expected an error");
+            assert_eq!(error.kind, kind);
+        }
+    }
+
+    #[test]
+    fn error_location() {
+        let mut machine = Machine::with_program(vec![1101, 1, 1, 5, 1301, 0, 0, 0, 99]);
+        let error = machine.execute().expect_err("expected an error");
+        assert_eq!(error.program_counter, 4);
+        assert_eq!(error.instruction, 1301);
+        assert_eq!(machine.read_program_counter(), 4);
+    }
+
+    #[test]
+    fn output_closed() {
+        let (output_tx, output_rx) = mpsc::channel();
+        drop(output_rx);
+        let mut machine = Machine::new(vec![104, 1, 99], Vec::new(), output_tx);
+        assert_eq!(
+            machine.execute().expect_err("expected an error").kind,
+            MachineErrorKind::OutputClosed
+        );
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut machine = Machine::with_program(vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        assert_eq!(
+            machine.run_until_io().expect("failed to run"),
+            ExecutionState::NeedsInput
+        );
+        let snapshot = machine.snapshot();
+        machine.push_input(1);
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.output(), &vec![2]);
+
+        machine.restore(snapshot.clone());
+        machine.push_input(10);
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.output(), &vec![2, 11]);
+
+        let mut restored = Machine::from_snapshot(snapshot, vec![20], Vec::new());
+        restored.execute().expect("failed to execute");
+        assert_eq!(restored.into_output(), vec![21]);
+    }
+
+    #[test]
+    fn fork_with_clone() {
+        let mut machine = Machine::with_program(vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]);
+        machine.run_until_io().expect("failed to run");
+        let mut forks: Vec<_> = (0..3).map(|_| machine.clone()).collect();
+        for (i, fork) in forks.iter_mut().enumerate() {
+            fork.push_input(i as i64);
+            fork.execute().expect("failed to execute");
+        }
+        let outputs: Vec<_> = forks.into_iter().map(|fork| fork.into_output()).collect();
+        assert_eq!(outputs, vec![vec![1], vec![2], vec![3]]);
+        assert_eq!(machine.read_program_counter(), 0);
+    }
+
+    #[test]
+    fn decode_cache_self_modifying() {
+        // rewrites the operand of its first instruction so the second pass outputs 2.
+        let program = assemble(
+            "
+            start:  out #1
+                    add #1, #1, [start+1]
+                    add [n], #-1, [n]
+                    jt [n], #start
+                    hlt
+            n:      data 2
+            ",
+        )
+        .expect("failed to assemble");
+        for cached in [true, false] {
+            let mut machine = Machine::with_program(program.clone()).with_decode_cache(cached);
+            machine.execute().expect("failed to execute");
+            assert_eq!(machine.into_output(), vec![1, 2]);
+        }
+    }
+
+    #[test]
+    fn profiles() {
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::with_program(quine).with_profile(Profile::Day5);
+        let error = machine.execute().expect_err("day 5 has no relative base");
+        assert_eq!(
+            (error.program_counter, error.kind),
+            (0, MachineErrorKind::UnknownOpcode(9))
+        );
+        // the error keeps the whole word.
+        assert_eq!(error.instruction, 109);
+
+        let mut machine =
+            Machine::with_program(vec![1101, 1, 1, 0, 99]).with_profile(Profile::Day2);
+        let error = machine.execute().expect_err("day 2 has no immediate mode");
+        assert_eq!(
+            error.kind,
+            MachineErrorKind::InvalidAddressingMode {
+                operand: 1,
+                mode: 1
+            }
+        );
+        let mut machine = Machine::with_program(vec![1, 0, 0, 0, 99]).with_profile(Profile::Day2);
+        machine.execute().expect("failed to execute");
+
+        let program = vec![1, 0, 0, 10, 99];
+        let mut machine = Machine::with_program(program.clone()).with_profile(Profile::Day5);
+        let error = machine.execute().expect_err("day 5 memory doesn't grow");
+        assert_eq!(
+            error.kind,
+            MachineErrorKind::AddressOutOfBounds {
+                address: 10,
+                limit: 5
+            }
+        );
+        let mut machine = Machine::with_program(program);
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.read_address(10), 2);
+
+        let mut machine =
+            Machine::with_program(vec![1, 0, 0, 0, 204, 0, 99]).with_profile(Profile::Day5);
+        let error = machine.execute().expect_err("day 5 has no relative mode");
+        assert_eq!(
+            (error.program_counter, error.kind),
+            (
+                4,
+                MachineErrorKind::InvalidAddressingMode {
+                    operand: 1,
+                    mode: 2
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        // squares its input and outputs the result.
+        let program = vec![3, 9, 2, 9, 9, 10, 4, 10, 99, 0, 0];
+        let mut machine = Machine::new(program.clone(), vec![1 << 31], Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output(), vec![1 << 62]);
+        let mut machine = Machine::new(program.clone(), vec![1 << 32], Vec::new());
+        let error = machine.execute().expect_err("expected an overflow");
+        assert_eq!(
+            (error.program_counter, error.kind),
+            (2, MachineErrorKind::Overflow)
+        );
+        assert_eq!(machine.read_address(10), 0);
+
+        let mut machine = Machine::from_words(words(&program), vec![Wrapping(1 << 32)], Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output(), vec![Wrapping(0)]);
+
+        let input: BigInt = "4294967296".parse().expect("failed to parse");
+        let mut machine = Machine::from_words(words(&program), vec![input], Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output()[0].to_string(), "18446744073709551616");
+
+        let mut machine = Machine::with_program(vec![109, i64::MAX, 109, 1, 99]);
+        let error = machine.execute().expect_err("expected an overflow");
+        assert_eq!(
+            (error.program_counter, error.kind),
+            (2, MachineErrorKind::Overflow)
+        );
+    }
+
+    #[test]
+    fn big_words() {
+        // outputs an immediate too big for an i64, then doubles it.
+        let big: BigInt = "123456789012345678901234567890"
+            .parse()
+            .expect("failed to parse");
+        let mut program: Vec<BigInt> = words(&[104, 0, 1102, 2, 0, 12, 4, 12, 99]);
+        program[1] = big.clone();
+        program[4] = big.clone();
+        let mut machine = Machine::from_words(program, Vec::new(), Vec::new());
+        machine.execute().expect("failed to execute");
+        let outputs: Vec<String> = machine.output().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            outputs,
+            vec![
+                "123456789012345678901234567890",
+                "246913578024691357802469135780"
+            ]
+        );
+
+        let mut machine = Machine::from_words(vec![big.clone()], Vec::new(), Vec::new());
+        let error = machine.execute().expect_err("expected an error");
+        assert_eq!(error.kind, MachineErrorKind::UnknownOpcode(i64::MAX));
+        assert_eq!(error.instruction, i64::MAX);
+
+        // day 9's large number examples give the same answers in every mode.
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let mut machine = Machine::from_words(words::<BigInt>(&quine), Vec::new(), Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output(), words::<BigInt>(&quine));
+        let program = vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0];
+        let mut machine = Machine::from_words(words::<BigInt>(&program), Vec::new(), Vec::new());
+        machine.execute().expect("failed to execute");
+        assert_eq!(machine.into_output()[0].to_string(), "1219070632396864");
+    }
 }
@@ -0,0 +1,117 @@
+use super::word::Word;
+
+pub static DEFAULT_MEMORY_LIMIT: usize = 1 << 20;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub address: usize,
+    pub limit: usize,
+}
+
+// Machine memory that starts as the loaded program and grows when written past its end.
+// Cells that were never written read as 0. Addresses at or above the limit are rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Memory<W = i64> {
+    cells: Vec<W>,
+    limit: usize,
+}
+
+impl<W: Word> Memory<W> {
+    pub fn new(program: Vec<W>) -> Self {
+        Memory {
+            cells: program,
+            limit: DEFAULT_MEMORY_LIMIT,
+        }
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    // Reads without bounds checking against the limit, used when fetching instructions.
+    pub fn get(&self, address: usize) -> W {
+        self.cells.get(address).cloned().unwrap_or_default()
+    }
+
+    pub fn read(&self, address: usize) -> Result<W, OutOfBounds> {
+        self.check(address)?;
+        Ok(self.get(address))
+    }
+
+    pub fn write(&mut self, address: usize, value: W) -> Result<(), OutOfBounds> {
+        self.check(address)?;
+        if address >= self.cells.len() {
+            self.cells.resize(address + 1, W::default());
+        }
+        self.cells[address] = value;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn as_vec(&self) -> &Vec<W> {
+        &self.cells
+    }
+
+    // Whether `address` may be read or written.
+    pub fn check(&self, address: usize) -> Result<(), OutOfBounds> {
+        if address >= self.limit && address >= self.cells.len() {
+            Err(OutOfBounds {
+                address,
+                limit: self.limit,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_on_write() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        assert_eq!(memory.read(100), Ok(0));
+        assert_eq!(memory.len(), 3);
+        memory.write(100, 7).expect("failed to write");
+        assert_eq!(memory.len(), 101);
+        assert_eq!(memory.read(100), Ok(7));
+        assert_eq!(memory.read(50), Ok(0));
+    }
+
+    #[test]
+    fn limit() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        memory.set_limit(10);
+        assert!(memory.write(9, 1).is_ok());
+        assert_eq!(
+            memory.write(10, 1),
+            Err(OutOfBounds {
+                address: 10,
+                limit: 10
+            })
+        );
+        assert!(memory.read(usize::MAX).is_err());
+        assert_eq!(memory.get(usize::MAX), 0);
+    }
+
+    #[test]
+    fn program_longer_than_limit() {
+        let mut memory = Memory::new(vec![0; 20]);
+        memory.set_limit(10);
+        assert!(memory.write(15, 1).is_ok());
+        assert!(memory.write(20, 1).is_err());
+    }
+}
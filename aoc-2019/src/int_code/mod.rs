@@ -1,14 +1,38 @@
 extern crate termion;
 
-use std::fs;
-use std::io::{self};
-
+pub mod analysis;
+pub mod ascii;
+pub mod assembler;
+pub mod compiler;
+#[cfg(test)]
+mod conformance;
+pub mod disassembler;
+pub mod executor;
+pub mod explore;
+pub mod instruction;
+pub mod io;
+pub mod loader;
 pub mod machine;
+pub mod memory;
 pub mod monitor;
+pub mod network;
+pub mod patch;
+pub mod pipeline;
+pub mod profile;
+pub mod recording;
+pub mod snapshot;
+pub mod trace;
+pub mod word;
+
+// Loads a program in either of the encodings `loader` understands, from stdin if `path` is
+// `-`.
+pub fn read_file(path: &str) -> std::io::Result<Vec<i64>> {
+    Ok(loader::load(path)?)
+}
 
-pub fn read_file(path: &str) -> io::Result<Vec<i64>> {
-    Ok(fs::read_to_string(path)?
-        .split(',')
-        .map(|x| x.parse::<i64>().unwrap())
-        .collect())
+// Loads a program from the crate's `fixtures` directory, wherever the tests are run from.
+#[cfg(test)]
+fn read_fixture(name: &str) -> Vec<i64> {
+    let path = format!("{}/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    read_file(&path).expect("failed to read fixture")
 }
@@ -1,17 +1,19 @@
+use super::io::Output;
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::io::{stdout, Write};
+use std::io::{self, stdout, Stdout, Write};
 use std::sync::mpsc::Receiver;
-use termion::raw::IntoRawMode;
+use termion::raw::{IntoRawMode, RawTerminal};
 use termion::{color, cursor};
 
-#[derive(Copy, Clone, Debug)]
-struct Point {
-    x: u16,
-    y: u16,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
 }
 
-#[derive(Copy, Clone, Debug)]
-enum DisplayObject {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DisplayObject {
     Empty,
     Wall,
     Block,
@@ -34,21 +36,33 @@ impl TryFrom<i64> for DisplayObject {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum Instruction {
+impl DisplayObject {
+    // How the object is drawn in a plain text snapshot.
+    pub fn symbol(&self) -> char {
+        match self {
+            DisplayObject::Empty => ' ',
+            DisplayObject::Wall => '#',
+            DisplayObject::Block => 'x',
+            DisplayObject::HorizPaddle => '-',
+            DisplayObject::Ball => 'o',
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
     Display(Point, DisplayObject),
     Score(i64),
 }
 
 impl Instruction {
-    fn new(f: i64, s: i64, arg: i64) -> Option<Self> {
+    pub fn new(f: i64, s: i64, arg: i64) -> Option<Self> {
         match (f, s, DisplayObject::try_from(arg)) {
             (-1, 0, _) => Some(Instruction::Score(arg)),
-            // we add one as termion is 1,1 based not from 0,0 as the monitor is.
-            (x, y, Ok(obj)) => Some(Instruction::Display(
+            (x, y, Ok(obj)) if x >= 0 && y >= 0 => Some(Instruction::Display(
                 Point {
-                    x: (x + 1) as u16,
-                    y: (y + 1) as u16,
+                    x: x as usize,
+                    y: y as usize,
                 },
                 obj,
             )),
@@ -57,58 +71,342 @@ impl Instruction {
     }
 }
 
-pub struct Monitor {
-    input: Receiver<i64>,
+// The frame buffer: every tile drawn so far and the last score.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Screen {
+    tiles: HashMap<Point, DisplayObject>,
+    width: usize,
+    height: usize,
+    score: Option<i64>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Screen::default()
+    }
+
+    pub fn apply(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Display(point, obj) => {
+                self.width = self.width.max(point.x + 1);
+                self.height = self.height.max(point.y + 1);
+                self.tiles.insert(point, obj);
+            }
+            Instruction::Score(score) => self.score = Some(score),
+        }
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> DisplayObject {
+        self.tiles
+            .get(&Point { x, y })
+            .copied()
+            .unwrap_or(DisplayObject::Empty)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn score(&self) -> Option<i64> {
+        self.score
+    }
+
+    pub fn count(&self, obj: DisplayObject) -> usize {
+        self.tiles.values().filter(|tile| **tile == obj).count()
+    }
+
+    // Where `obj` is drawn, if it is on screen. Useful for the ball and paddle which only
+    // appear once.
+    pub fn find(&self, obj: DisplayObject) -> Option<Point> {
+        self.tiles
+            .iter()
+            .find(|(_, tile)| **tile == obj)
+            .map(|(point, _)| *point)
+    }
 }
 
-impl Monitor {
-    pub fn new(input: Receiver<i64>) -> Self {
-        Monitor { input }
+// Draws the screen as text, one line per row followed by the score if there is one.
+impl std::fmt::Display for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for y in 0..self.height {
+            let row: String = (0..self.width).map(|x| self.tile(x, y).symbol()).collect();
+            writeln!(f, "{}", row)?;
+        }
+        if let Some(score) = self.score {
+            writeln!(f, "score: {}", score)?;
+        }
+        Ok(())
     }
+}
 
-    pub fn start(&self) {
-        let mut stdout = stdout()
-            .into_raw_mode()
-            .expect("failed to get raw terminal");
+// Somewhere to show the screen. `update` is called after each instruction has been applied
+// and `finish` once the program has stopped drawing.
+pub trait Backend {
+    fn update(&mut self, screen: &Screen, instruction: Instruction) -> io::Result<()>;
+
+    fn finish(&mut self, _screen: &Screen) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Keeps nothing but the monitor's own screen, for tests and running without a terminal.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Headless;
+
+impl Backend for Headless {
+    fn update(&mut self, _screen: &Screen, _instruction: Instruction) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Writes the final screen as plain text.
+#[derive(Debug)]
+pub struct TextSnapshot<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TextSnapshot<W> {
+    pub fn new(writer: W) -> Self {
+        TextSnapshot { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Backend for TextSnapshot<W> {
+    fn update(&mut self, _screen: &Screen, _instruction: Instruction) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self, screen: &Screen) -> io::Result<()> {
+        write!(self.writer, "{}", screen)?;
+        self.writer.flush()
+    }
+}
+
+// Draws each tile as it changes on a raw terminal, with the score below the game.
+pub struct Terminal {
+    stdout: RawTerminal<Stdout>,
+}
+
+impl Terminal {
+    pub fn new() -> std::io::Result<Self> {
+        let mut stdout = stdout().into_raw_mode()?;
         write!(
             stdout,
             "{}{}{}",
             termion::clear::All,
             cursor::Goto(1, 1),
             termion::cursor::Hide
-        )
-        .unwrap();
-        loop {
-            let instruction = match (self.input.recv(), self.input.recv(), self.input.recv()) {
-                (Ok(x), Ok(y), Ok(z)) => match Instruction::new(x, y, z) {
-                    Some(ins) => ins,
-                    None => panic!("failed to parse {},{},{} into instruction", x, y, z),
-                },
-                (Ok(i), Ok(j), Err(e)) => panic!("partial instruction {},{} e = {}", i, j, e),
-                (Ok(i), Err(e), _) => panic!("partial instruction {} e = {}", i, e),
-                _ => break,
-            };
-            match instruction {
-                Instruction::Display(position, obj) => {
-                    let pos = cursor::Goto(position.x, position.y);
-                    match obj {
-                        DisplayObject::Empty => {
-                            write!(stdout, "{}{} ", color::Fg(color::Reset), pos)
-                        }
-                        DisplayObject::Ball => write!(stdout, "{}{}●", color::Fg(color::Blue), pos),
-                        DisplayObject::Wall => {
-                            write!(stdout, "{}{}█", color::Fg(color::Rgb(211, 211, 211)), pos)
-                        }
-                        DisplayObject::Block => write!(stdout, "{}{}█", color::Fg(color::Red), pos),
-                        DisplayObject::HorizPaddle => {
-                            write!(stdout, "{}{}━", color::Fg(color::Yellow), pos)
-                        }
-                    };
+        )?;
+        Ok(Terminal { stdout })
+    }
+
+    fn _draw_score(&mut self, screen: &Screen) -> io::Result<()> {
+        if let Some(score) = screen.score() {
+            // termion is 1,1 based, so the row after the game is height + 2.
+            let row = (screen.height() + 2) as u16;
+            write!(
+                self.stdout,
+                "{}{}{}score: {}",
+                cursor::Goto(1, row),
+                color::Fg(color::Reset),
+                termion::clear::CurrentLine,
+                score
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Backend for Terminal {
+    fn update(&mut self, screen: &Screen, instruction: Instruction) -> io::Result<()> {
+        let stdout = &mut self.stdout;
+        match instruction {
+            Instruction::Display(position, obj) => {
+                // we add one as termion is 1,1 based not from 0,0 as the monitor is.
+                let pos = cursor::Goto(position.x as u16 + 1, position.y as u16 + 1);
+                match obj {
+                    DisplayObject::Empty => {
+                        write!(stdout, "{}{} ", color::Fg(color::Reset), pos)
+                    }
+                    DisplayObject::Ball => write!(stdout, "{}{}●", color::Fg(color::Blue), pos),
+                    DisplayObject::Wall => {
+                        write!(stdout, "{}{}█", color::Fg(color::Rgb(211, 211, 211)), pos)
+                    }
+                    DisplayObject::Block => write!(stdout, "{}{}█", color::Fg(color::Red), pos),
+                    DisplayObject::HorizPaddle => {
+                        write!(stdout, "{}{}━", color::Fg(color::Yellow), pos)
+                    }
+                }?;
+                // the game may have grown over the score.
+                if position.y + 1 == screen.height() {
+                    self._draw_score(screen)?;
                 }
-                _ => {}
+            }
+            Instruction::Score(_) => self._draw_score(screen)?,
+        }
+        self.stdout.flush()
+    }
+
+    fn finish(&mut self, screen: &Screen) -> io::Result<()> {
+        let row = (screen.height() + 3) as u16;
+        write!(
+            self.stdout,
+            "{}{}{}",
+            color::Fg(color::Reset),
+            cursor::Goto(1, row),
+            termion::cursor::Show
+        )?;
+        self.stdout.flush()
+    }
+}
+
+pub struct Monitor<B> {
+    screen: Screen,
+    backend: B,
+    pending: Vec<i64>,
+}
+
+impl<B: Backend> Monitor<B> {
+    pub fn new(backend: B) -> Self {
+        Monitor {
+            screen: Screen::new(),
+            backend,
+            pending: Vec::with_capacity(3),
+        }
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    // Takes one value output by the program, returning the instruction it completes. Fails
+    // if the values don't make an instruction or the backend can't draw it.
+    pub fn push(&mut self, value: i64) -> Result<Option<Instruction>, String> {
+        self.pending.push(value);
+        if self.pending.len() < 3 {
+            return Ok(None);
+        }
+        let (x, y, z) = (self.pending[0], self.pending[1], self.pending[2]);
+        self.pending.clear();
+        let instruction = Instruction::new(x, y, z)
+            .ok_or_else(|| format!("failed to parse {},{},{} into instruction", x, y, z))?;
+        self.screen.apply(instruction);
+        self.backend
+            .update(&self.screen, instruction)
+            .map_err(|e| format!("failed to draw: {}", e))?;
+        Ok(Some(instruction))
+    }
+
+    // Tells the backend that the program has stopped drawing.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.backend.finish(&self.screen)
+    }
+
+    // Draws everything sent on `input` until the sender hangs up.
+    pub fn start(&mut self, input: Receiver<i64>) {
+        for value in input.iter() {
+            if let Err(e) = self.push(value) {
+                panic!("{}", e);
             }
         }
+        if !self.pending.is_empty() {
+            panic!("partial instruction {:?}", self.pending);
+        }
+        self.finish().expect("failed to draw");
+    }
+}
+
+// Lets a machine draw straight to the monitor. A bad instruction or a failed draw closes the
+// output.
+impl<B: Backend> Output for Monitor<B> {
+    fn write(&mut self, value: i64) -> bool {
+        self.push(value).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::machine::Machine;
+    use super::*;
+
+    #[test]
+    fn frame_buffer() {
+        let program = vec![
+            104, 1, 104, 2, 104, 3, 104, 6, 104, 5, 104, 4, 104, -1, 104, 0, 104, 12345, 104, 1,
+            104, 2, 104, 0, 99,
+        ];
+        let mut machine = Machine::new(program, Vec::new(), Monitor::new(Headless));
+        machine.execute().expect("failed to execute");
+        let monitor = machine.into_output();
+        let screen = monitor.screen();
+        assert_eq!(screen.tile(6, 5), DisplayObject::Ball);
+        assert_eq!(screen.tile(1, 2), DisplayObject::Empty);
+        assert_eq!(screen.find(DisplayObject::Ball), Some(Point { x: 6, y: 5 }));
+        assert_eq!(screen.count(DisplayObject::HorizPaddle), 0);
+        assert_eq!((screen.width(), screen.height()), (7, 6));
+        assert_eq!(screen.score(), Some(12345));
+    }
+
+    #[test]
+    fn text_snapshot() {
+        let mut monitor = Monitor::new(TextSnapshot::new(Vec::new()));
+        for value in [
+            0, 0, 1, 1, 0, 1, 2, 0, 1, 0, 1, 1, 1, 1, 4, 2, 1, 2, 0, 2, 3, -1, 0, 7,
+        ] {
+            monitor.push(value).expect("bad instruction");
+        }
+        monitor.finish().expect("failed to draw");
+        let text = String::from_utf8(monitor.into_backend().into_inner()).expect("text is utf8");
+        assert_eq!(text, "###\n#ox\n-  \nscore: 7\n");
+    }
+
+    // A writer whose reader has gone away.
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failed_draw() {
+        let mut monitor = Monitor::new(TextSnapshot::new(Closed));
+        for value in [0, 0, 1] {
+            monitor.push(value).expect("bad instruction");
+        }
+        let error = monitor.finish().expect_err("expected a broken pipe");
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    }
 
-        write!(stdout, "{}", termion::cursor::Show).unwrap();
+    #[test]
+    fn bad_instruction() {
+        let mut monitor = Monitor::new(Headless);
+        assert_eq!(monitor.push(3), Ok(None));
+        assert_eq!(monitor.push(4), Ok(None));
+        assert!(monitor.push(9).is_err());
+        assert!(monitor.push(-2).is_ok());
+        assert!(monitor.push(0).is_ok());
+        assert!(monitor.push(0).is_err());
     }
 }
@@ -0,0 +1,436 @@
+use super::io::Input;
+use super::machine::{ExecutionState, Machine, MachineError};
+use std::collections::VecDeque;
+
+// Instructions a machine may run before the scheduler moves on to the next one.
+static TIME_SLICE: usize = 1000;
+// A machine counts as idle once this many of its slices in a row have ended on a read from an
+// empty queue, without it sending anything.
+static IDLE_READS: usize = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Packet {
+    pub address: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    // A machine output a packet, including packets to the NAT.
+    Sent { from: usize, packet: Packet },
+    // A packet was addressed to a machine that isn't on the network.
+    Dropped { from: usize, packet: Packet },
+    // The network was idle so the NAT sent its last packet to machine 0.
+    NatDelivered(Packet),
+    // The network was idle and the NAT had nothing to send, or there is no NAT. Nothing can
+    // wake the network after this, so it stops until another packet is sent to it.
+    Idle,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NetworkError {
+    pub machine: usize,
+    pub error: MachineError,
+}
+
+impl std::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "machine {}: {}", self.machine, self.error)
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// A network card's receive queue. Reading an empty queue gives -1 rather than blocking.
+#[derive(Clone, Debug)]
+struct Receiver {
+    queue: VecDeque<i64>,
+    empty_reads: usize,
+}
+
+impl Input for Receiver {
+    fn read(&mut self) -> Option<i64> {
+        match self.queue.pop_front() {
+            Some(value) => {
+                self.empty_reads = 0;
+                Some(value)
+            }
+            None => {
+                self.empty_reads += 1;
+                Some(-1)
+            }
+        }
+    }
+}
+
+// Runs one copy of a program per address. Each machine is given its address as its first
+// input, then sends packets by outputting address, x, y. Machines are run in address order
+// on the calling thread, so a network always produces the same events.
+#[derive(Debug)]
+pub struct Network {
+    machines: Vec<Machine<Receiver, Vec<i64>>>,
+    halted: Vec<bool>,
+    nat_address: Option<i64>,
+    nat: Option<Packet>,
+    events: VecDeque<Event>,
+    // Set when `Idle` is reported, and cleared when a packet is sent.
+    stopped: bool,
+}
+
+impl Network {
+    pub fn new(program: &[i64], size: usize) -> Self {
+        let machines = (0..size)
+            .map(|address| {
+                let input = Receiver {
+                    queue: VecDeque::from(vec![address as i64]),
+                    empty_reads: 0,
+                };
+                Machine::new(program.to_vec(), input, Vec::new())
+            })
+            .collect();
+        Network {
+            machines,
+            halted: vec![false; size],
+            nat_address: None,
+            nat: None,
+            events: VecDeque::new(),
+            stopped: false,
+        }
+    }
+
+    // Adds a NAT at `address` which keeps the last packet sent to it and sends that packet
+    // to machine 0 whenever the network is idle.
+    pub fn with_nat(mut self, address: i64) -> Self {
+        self.nat_address = Some(address);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    // The last packet sent to the NAT.
+    pub fn nat_packet(&self) -> Option<Packet> {
+        self.nat
+    }
+
+    // Queues a packet as if it had been sent by another machine, returning false if there is
+    // no machine at its address.
+    pub fn send(&mut self, packet: Packet) -> bool {
+        match self._receiver(packet.address) {
+            Some(receiver) => {
+                receiver.queue.extend([packet.x, packet.y]);
+                self.stopped = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn _receiver(&mut self, address: i64) -> Option<&mut Receiver> {
+        if address < 0 {
+            return None;
+        }
+        self.machines
+            .get_mut(address as usize)
+            .map(|machine| machine.input_mut())
+    }
+
+    fn _route(&mut self, from: usize, packet: Packet) {
+        self.events.push_back(Event::Sent { from, packet });
+        if Some(packet.address) == self.nat_address {
+            self.nat = Some(packet);
+        } else if !self.send(packet) {
+            self.events.push_back(Event::Dropped { from, packet });
+        }
+    }
+
+    // Runs machine `index` until it reads an empty queue, halts or uses up its slice. A
+    // machine that uses up its slice is busy, however many empty reads came before.
+    fn _run(&mut self, index: usize) -> Result<(), NetworkError> {
+        for _ in 0..TIME_SLICE {
+            let machine = &mut self.machines[index];
+            let empty_reads = machine.input_mut().empty_reads;
+            let state = machine.step().map_err(|error| NetworkError {
+                machine: index,
+                error,
+            })?;
+            match state {
+                ExecutionState::Halted | ExecutionState::NeedsInput => {
+                    self.halted[index] = true;
+                    return Ok(());
+                }
+                ExecutionState::Output(_) if machine.output().len() == 3 => {
+                    let words: Vec<i64> = machine.output_mut().drain(..).collect();
+                    machine.input_mut().empty_reads = 0;
+                    let packet = Packet {
+                        address: words[0],
+                        x: words[1],
+                        y: words[2],
+                    };
+                    self._route(index, packet);
+                }
+                ExecutionState::Running if machine.input_mut().empty_reads > empty_reads => {
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        self.machines[index].input_mut().empty_reads = 0;
+        Ok(())
+    }
+
+    fn _idle(&mut self) -> bool {
+        let halted = &self.halted;
+        !halted.iter().all(|halted| *halted)
+            && self
+                .machines
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| !halted[*index])
+                .all(|(_, machine)| {
+                    let receiver = machine.input_mut();
+                    receiver.queue.is_empty() && receiver.empty_reads >= IDLE_READS
+                })
+    }
+
+    // Runs the network until something happens, returning None once every machine has halted
+    // or once the network has stopped after reporting `Idle`.
+    pub fn next_event(&mut self) -> Result<Option<Event>, NetworkError> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.stopped || self.halted.iter().all(|halted| *halted) {
+                return Ok(None);
+            }
+            for index in 0..self.machines.len() {
+                if !self.halted[index] {
+                    self._run(index)?;
+                }
+            }
+            if self.events.is_empty() && self._idle() {
+                let event = match self.nat {
+                    Some(packet) => {
+                        let packet = Packet {
+                            address: 0,
+                            ..packet
+                        };
+                        self.send(packet);
+                        Event::NatDelivered(packet)
+                    }
+                    None => {
+                        self.stopped = true;
+                        Event::Idle
+                    }
+                };
+                self.events.push_back(event);
+            }
+        }
+    }
+
+    // Runs the network until `stop` returns true for an event, returning that event, or None
+    // if the network halts or stops first. A network without a NAT stops the first time it's
+    // idle.
+    pub fn run_until<F>(&mut self, mut stop: F) -> Result<Option<Event>, NetworkError>
+    where
+        F: FnMut(&Event) -> bool,
+    {
+        while let Some(event) = self.next_event()? {
+            if stop(&event) {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::*;
+
+    // Machines other than 0 send a packet to the NAT containing their address and then wait.
+    // Machine 0 replies to each packet it receives by sending it to the NAT with y + 1.
+    static PROGRAM: &str = "
+                in [addr]
+                jt [addr], #sender
+        loop:   in [x]
+                eq [x], #-1, [t]
+                jt [t], #loop
+                in [y]
+                add [y], #1, [y]
+                out #255
+                out [x]
+                out [y]
+                jf #0, #loop
+        sender: out #255
+                out [addr]
+                out #0
+        wait:   in [x]
+                jf #0, #wait
+        addr:   data 0
+        x:      data 0
+        y:      data 0
+        t:      data 0
+        ";
+
+    #[test]
+    fn packets_to_nat() {
+        let program = assemble(PROGRAM).expect("failed to assemble");
+        let mut network = Network::new(&program, 4).with_nat(255);
+        let mut sent = Vec::new();
+        network
+            .run_until(|event| match event {
+                Event::Sent { from, packet } => {
+                    sent.push((*from, packet.x));
+                    sent.len() == 3
+                }
+                _ => false,
+            })
+            .expect("network failed");
+        assert_eq!(sent, vec![(1, 1), (2, 2), (3, 3)]);
+        assert_eq!(
+            network.nat_packet(),
+            Some(Packet {
+                address: 255,
+                x: 3,
+                y: 0
+            })
+        );
+    }
+
+    #[test]
+    fn nat_wakes_idle_network() {
+        let program = assemble(PROGRAM).expect("failed to assemble");
+        let mut network = Network::new(&program, 2).with_nat(255);
+        let mut delivered = Vec::new();
+        network
+            .run_until(|event| match event {
+                Event::NatDelivered(packet) => {
+                    delivered.push(packet.y);
+                    delivered.len() == 3
+                }
+                _ => false,
+            })
+            .expect("network failed");
+        assert_eq!(delivered, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn idle_without_nat() {
+        let program = assemble(PROGRAM).expect("failed to assemble");
+        let mut network = Network::new(&program, 2);
+        let mut events = Vec::new();
+        network
+            .run_until(|event| {
+                events.push(*event);
+                *event == Event::Idle
+            })
+            .expect("network failed");
+        let packet = Packet {
+            address: 255,
+            x: 1,
+            y: 0,
+        };
+        assert_eq!(
+            events,
+            vec![
+                Event::Sent { from: 1, packet },
+                Event::Dropped { from: 1, packet },
+                Event::Idle
+            ]
+        );
+    }
+
+    #[test]
+    fn busy_after_empty_reads() {
+        // machine 0 reads an empty queue twice and then computes for several slices before
+        // sending, after machine 1 has finished computing and gone idle.
+        let program = assemble(
+            "
+                    in [addr]
+                    jt [addr], #busy
+                    in [x]
+                    in [x]
+                    mul [n], #2, [n]
+            busy:   add [n], #-1, [n]
+                    jt [n], #busy
+                    jt [addr], #wait
+                    out #255
+                    out #0
+                    out #0
+            wait:   in [x]
+                    jf #0, #wait
+            addr:   data 0
+            x:      data 0
+            n:      data 1500
+            ",
+        )
+        .expect("failed to assemble");
+        let mut network = Network::new(&program, 2);
+        let mut events = Vec::new();
+        while let Some(event) = network.next_event().expect("network failed") {
+            events.push(event);
+        }
+        let packet = Packet {
+            address: 255,
+            x: 0,
+            y: 0,
+        };
+        assert_eq!(
+            events,
+            vec![
+                Event::Sent { from: 0, packet },
+                Event::Dropped { from: 0, packet },
+                Event::Idle
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_when_idle() {
+        let program = assemble(PROGRAM).expect("failed to assemble");
+        let mut network = Network::new(&program, 2);
+        let mut events = Vec::new();
+        let last = network
+            .run_until(|event| {
+                events.push(*event);
+                false
+            })
+            .expect("network failed");
+        assert_eq!(last, None);
+        assert_eq!(events.last(), Some(&Event::Idle));
+
+        // a packet from outside wakes it up again.
+        assert!(network.send(Packet {
+            address: 0,
+            x: 4,
+            y: 5
+        }));
+        let event = network.next_event().expect("network failed");
+        assert!(matches!(event, Some(Event::Sent { from: 0, .. })));
+    }
+
+    #[test]
+    fn halted_network() {
+        let mut network = Network::new(&[104, 0, 104, 5, 104, 6, 99], 2);
+        let mut sent = Vec::new();
+        while let Some(event) = network.next_event().expect("network failed") {
+            if let Event::Sent { from, .. } = event {
+                sent.push(from);
+            }
+        }
+        assert_eq!(sent, vec![0, 1]);
+        assert_eq!(network.len(), 2);
+    }
+}
@@ -0,0 +1,198 @@
+use super::machine::{ExecutionState, Machine, MachineError, MachineErrorKind};
+use super::profile::Profile;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+pub type PatchedMachine = Machine<VecDeque<i64>, Vec<i64>>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    Machine(MachineError),
+    // The program was still running after its step budget.
+    OutOfSteps,
+    // The number of combinations to search doesn't fit in a `usize`.
+    TooManyCombinations,
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatchError::Machine(error) => write!(f, "{}", error),
+            PatchError::OutOfSteps => write!(f, "program didn't halt within its step budget"),
+            PatchError::TooManyCombinations => write!(f, "too many combinations to search"),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatchError::Machine(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<MachineError> for PatchError {
+    fn from(error: MachineError) -> Self {
+        PatchError::Machine(error)
+    }
+}
+
+// Runs `program` to completion under `profile` with each `(address, value)` patch written
+// first, returning the halted machine so that any address can be read back. Gives up after
+// `steps` instructions, since a patched program may never halt.
+pub fn run_patched(
+    program: &[i64],
+    profile: Profile,
+    patches: &[(usize, i64)],
+    steps: usize,
+) -> Result<PatchedMachine, PatchError> {
+    let mut machine = Machine::with_program(program.to_vec()).with_profile(profile);
+    for (address, value) in patches {
+        machine.write_address(*address, *value)?;
+    }
+    for _ in 0..steps {
+        match machine.step()? {
+            ExecutionState::Halted => return Ok(machine),
+            // there's no input to give.
+            ExecutionState::NeedsInput => {
+                return Err(machine.error(MachineErrorKind::InputClosed).into())
+            }
+            _ => {}
+        }
+    }
+    Err(PatchError::OutOfSteps)
+}
+
+// The `index`th combination of `values` at each address, counting with the first address
+// as the most significant digit.
+fn _combination(index: usize, values: &[i64], addresses: usize) -> Vec<i64> {
+    let mut rest = index;
+    let mut combination = vec![0; addresses];
+    for slot in combination.iter_mut().rev() {
+        *slot = values[rest % values.len()];
+        rest /= values.len();
+    }
+    combination
+}
+
+// Tries every combination of `values` at `addresses` across all available cores, returning
+// the first combination, in order, whose halted machine `accept`s. Runs that fail or take
+// more than `steps` instructions count as not accepted.
+//
+//     // day 2: find the noun and verb that leave 19690720 at address 0
+//     let values: Vec<i64> = (0..100).collect();
+//     search(&program, Profile::Day2, &[1, 2], &values, 10_000, |machine| {
+//         machine.read_address(0) == 19_690_720
+//     })
+pub fn search<F>(
+    program: &[i64],
+    profile: Profile,
+    addresses: &[usize],
+    values: &[i64],
+    steps: usize,
+    accept: F,
+) -> Result<Option<Vec<i64>>, PatchError>
+where
+    F: Fn(&PatchedMachine) -> bool + Sync,
+{
+    if values.is_empty() {
+        return Ok(None);
+    }
+    let total = u32::try_from(addresses.len())
+        .ok()
+        .and_then(|count| values.len().checked_pow(count))
+        .ok_or(PatchError::TooManyCombinations)?;
+    let threads = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .min(total);
+    // the lowest accepted index so far, so that threads can stop once they pass it.
+    let best = AtomicUsize::new(usize::MAX);
+    thread::scope(|scope| {
+        for start in 0..threads {
+            let (best, accept) = (&best, &accept);
+            scope.spawn(move || {
+                for index in (start..total).step_by(threads) {
+                    if index > best.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let combination = _combination(index, values, addresses.len());
+                    let patches: Vec<(usize, i64)> =
+                        addresses.iter().copied().zip(combination).collect();
+                    if let Ok(machine) = run_patched(program, profile, &patches, steps) {
+                        if accept(&machine) {
+                            best.fetch_min(index, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    Ok(match best.into_inner() {
+        usize::MAX => None,
+        index => Some(_combination(index, values, addresses.len())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // [0] = [9] * [10] + [11], with both operands patched in.
+    static PROGRAM: [i64; 12] = [2, 9, 10, 0, 1, 0, 11, 0, 99, 0, 0, 7];
+
+    // loops forever unless [5] is patched to 0.
+    static LOOP: [i64; 8] = [1105, 1, 3, 1105, 1, 3, 99, 42];
+
+    #[test]
+    fn patches() {
+        let machine = run_patched(&PROGRAM, Profile::Day2, &[(9, 6), (10, 5)], 100)
+            .expect("failed to execute");
+        assert_eq!(machine.read_address(0), 37);
+        assert_eq!(machine.read_address(100), 0);
+        assert!(run_patched(&PROGRAM, Profile::Day2, &[(0, 42)], 100).is_err());
+        assert!(run_patched(&PROGRAM, Profile::Day2, &[(usize::MAX, 1)], 100).is_err());
+        assert_eq!(
+            run_patched(&PROGRAM, Profile::Day2, &[(9, 6), (10, 5)], 2).err(),
+            Some(PatchError::OutOfSteps)
+        );
+        match run_patched(&[3, 0, 99], Profile::Day9, &[], 100) {
+            Err(PatchError::Machine(error)) => assert_eq!(
+                (error.program_counter, error.kind),
+                (0, MachineErrorKind::InputClosed)
+            ),
+            other => panic!("expected the input to be closed, found {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn searches_in_order() {
+        let values: Vec<i64> = (0..20).collect();
+        let found = search(&PROGRAM, Profile::Day2, &[9, 10], &values, 100, |machine| {
+            machine.read_address(0) == 19
+        });
+        // 1 * 12 and 2 * 6 both work, the first address counts first.
+        assert_eq!(found, Ok(Some(vec![1, 12])));
+        let found = search(&PROGRAM, Profile::Day2, &[9, 10], &values, 100, |machine| {
+            machine.read_address(0) == 1000
+        });
+        assert_eq!(found, Ok(None));
+    }
+
+    #[test]
+    fn search_limits() {
+        // every value but 6 leaves the program looping.
+        let found = search(&LOOP, Profile::Day9, &[5], &[3, 4, 6], 100, |_| true);
+        assert_eq!(found, Ok(Some(vec![6])));
+        let addresses = vec![0; 64];
+        assert_eq!(
+            search(&PROGRAM, Profile::Day2, &addresses, &[0, 1], 100, |_| true),
+            Err(PatchError::TooManyCombinations)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use super::machine::{ExecutionState, Machine, MachineError};
+use std::collections::VecDeque;
+
+// How the stages of a pipeline are connected. Stages are numbered in the order of their
+// phase settings and stage 0 receives the pipeline's input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Topology {
+    // Each stage feeds the next.
+    Chain,
+    // A chain whose last stage also feeds the first.
+    Ring,
+    // Edges from one stage to another. A stage with several successors sends each of them
+    // every value it outputs.
+    Graph(Vec<(usize, usize)>),
+}
+
+impl Topology {
+    fn edges(&self, stages: usize) -> Vec<(usize, usize)> {
+        let chain = (1..stages).map(|stage| (stage - 1, stage));
+        match self {
+            Topology::Chain => chain.collect(),
+            Topology::Ring if stages > 0 => chain.chain(Some((stages - 1, 0))).collect(),
+            Topology::Ring => Vec::new(),
+            Topology::Graph(edges) => edges.clone(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PipelineError {
+    // A stage in the topology or the output stage doesn't have a phase setting.
+    UnknownStage(usize),
+    Machine { stage: usize, error: MachineError },
+    // Every stage that hasn't halted is waiting for input that will never come.
+    Stalled,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PipelineError::UnknownStage(stage) => write!(f, "no phase for stage {}", stage),
+            PipelineError::Machine { stage, error } => write!(f, "stage {}: {}", stage, error),
+            PipelineError::Stalled => write!(f, "every stage is waiting for input"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Machine { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Runs one copy of a program per phase setting, each given its phase as its first input.
+// Stages are run in turn on the calling thread until every stage has halted.
+//
+//     let outputs = Pipeline::new(program, &[9, 8, 7, 6, 5])
+//         .with_topology(Topology::Ring)
+//         .with_input(0)
+//         .run()?;
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+    program: Vec<i64>,
+    phases: Vec<i64>,
+    topology: Topology,
+    inputs: Vec<i64>,
+    output_stage: Option<usize>,
+}
+
+impl Pipeline {
+    pub fn new(program: Vec<i64>, phases: &[i64]) -> Self {
+        Pipeline {
+            program,
+            phases: phases.to_vec(),
+            topology: Topology::Chain,
+            inputs: Vec::new(),
+            output_stage: None,
+        }
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    // Queues a value for stage 0 after its phase setting.
+    pub fn with_input(mut self, value: i64) -> Self {
+        self.inputs.push(value);
+        self
+    }
+
+    // The stage whose outputs are returned by `run`, the last stage by default.
+    pub fn with_output_stage(mut self, stage: usize) -> Self {
+        self.output_stage = Some(stage);
+        self
+    }
+
+    // Returns every value output by the output stage, including those fed back into the
+    // pipeline.
+    pub fn run(&self) -> Result<Vec<i64>, PipelineError> {
+        let stages = self.phases.len();
+        let output_stage = self.output_stage.unwrap_or(stages.saturating_sub(1));
+        let mut successors = vec![Vec::new(); stages];
+        for (from, to) in self.topology.edges(stages) {
+            match (from, to) {
+                (from, _) if from >= stages => return Err(PipelineError::UnknownStage(from)),
+                (_, to) if to >= stages => return Err(PipelineError::UnknownStage(to)),
+                (from, to) => successors[from].push(to),
+            }
+        }
+        if output_stage >= stages {
+            return Err(PipelineError::UnknownStage(output_stage));
+        }
+
+        let mut machines: Vec<Machine<VecDeque<i64>, Vec<i64>>> = self
+            .phases
+            .iter()
+            .map(|phase| {
+                let mut machine = Machine::with_program(self.program.clone());
+                machine.push_input(*phase);
+                machine
+            })
+            .collect();
+        for value in &self.inputs {
+            machines[0].push_input(*value);
+        }
+
+        let mut halted = vec![false; stages];
+        let mut outputs = Vec::new();
+        while halted.iter().any(|halted| !halted) {
+            let mut progressed = false;
+            for stage in 0..stages {
+                if halted[stage] {
+                    continue;
+                }
+                let program_counter = machines[stage].read_program_counter();
+                loop {
+                    let state = machines[stage]
+                        .run_until_io()
+                        .map_err(|error| PipelineError::Machine { stage, error })?;
+                    match state {
+                        ExecutionState::Output(value) => {
+                            machines[stage].output_mut().clear();
+                            for successor in &successors[stage] {
+                                machines[*successor].push_input(value);
+                            }
+                            if stage == output_stage {
+                                outputs.push(value);
+                            }
+                            progressed = true;
+                        }
+                        ExecutionState::Halted => {
+                            halted[stage] = true;
+                            progressed = true;
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+                progressed |= machines[stage].read_program_counter() != program_counter;
+            }
+            if !progressed {
+                return Err(PipelineError::Stalled);
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // outputs the sum of its two inputs.
+    fn adder() -> Vec<i64> {
+        vec![3, 11, 3, 12, 1, 11, 12, 11, 4, 11, 99, 0, 0]
+    }
+
+    #[test]
+    fn day7_chain() {
+        let program = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        let outputs = Pipeline::new(program, &[4, 3, 2, 1, 0])
+            .with_input(0)
+            .run()
+            .expect("pipeline failed");
+        assert_eq!(outputs, vec![43210]);
+    }
+
+    #[test]
+    fn day7_ring() {
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        let outputs = Pipeline::new(program, &[9, 8, 7, 6, 5])
+            .with_topology(Topology::Ring)
+            .with_input(0)
+            .run()
+            .expect("pipeline failed");
+        assert_eq!(outputs.last(), Some(&139_629_729));
+    }
+
+    #[test]
+    fn graph() {
+        let pipeline = Pipeline::new(adder(), &[1, 2, 3, 4])
+            .with_topology(Topology::Graph(vec![(0, 1), (0, 2), (2, 3)]))
+            .with_input(10);
+        assert_eq!(pipeline.run(), Ok(vec![18]));
+        assert_eq!(pipeline.clone().with_output_stage(1).run(), Ok(vec![13]));
+    }
+
+    #[test]
+    fn errors() {
+        let pipeline = Pipeline::new(adder(), &[1, 2]);
+        assert_eq!(pipeline.run(), Err(PipelineError::Stalled));
+        assert_eq!(
+            pipeline
+                .clone()
+                .with_topology(Topology::Graph(vec![(0, 2)]))
+                .run(),
+            Err(PipelineError::UnknownStage(2))
+        );
+        assert_eq!(
+            pipeline.with_output_stage(5).run(),
+            Err(PipelineError::UnknownStage(5))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use super::instruction::Command;
+use super::machine::MachineErrorKind;
+
+// The Intcode features introduced by each puzzle. A machine running under an earlier profile
+// rejects instructions from later puzzles, so a program relying on them fails loudly instead
+// of running differently than the puzzle's own machine would.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    // add, mul and hlt with position operands only.
+    Day2,
+    // adds I/O, jumps and comparisons, and immediate operands.
+    Day5,
+    // adds the relative base and memory beyond the end of the program. The default.
+    #[default]
+    Day9,
+}
+
+impl Profile {
+    pub fn supports_opcode(&self, opcode: i64) -> bool {
+        match self {
+            Profile::Day2 => matches!(opcode, 1 | 2 | 99),
+            Profile::Day5 => matches!(opcode, 1..=8 | 99),
+            Profile::Day9 => matches!(opcode, 1..=9 | 99),
+        }
+    }
+
+    pub fn supports_mode(&self, mode: i64) -> bool {
+        match self {
+            Profile::Day2 => mode == 0,
+            Profile::Day5 => matches!(mode, 0 | 1),
+            Profile::Day9 => matches!(mode, 0..=2),
+        }
+    }
+
+    // Whether memory may grow past the end of the program.
+    pub fn grows_memory(&self) -> bool {
+        *self == Profile::Day9
+    }
+
+    // Fails with the error an older machine would have given for the instruction. Like
+    // decoding, an unsupported opcode is reported without its mode digits.
+    pub fn check(&self, command: &Command) -> Result<(), MachineErrorKind> {
+        if !self.supports_opcode(command.opcode()) {
+            return Err(MachineErrorKind::UnknownOpcode(command.opcode()));
+        }
+        for (i, operand) in command.operands().iter().enumerate() {
+            if !self.supports_mode(operand.mode()) {
+                return Err(MachineErrorKind::InvalidAddressingMode {
+                    operand: i + 1,
+                    mode: operand.mode(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day2" => Ok(Profile::Day2),
+            "day5" => Ok(Profile::Day5),
+            "day9" => Ok(Profile::Day9),
+            _ => Err(format!(
+                "unknown profile '{}', expected day2, day5 or day9",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::instruction::AddressingMode;
+    use super::*;
+
+    #[test]
+    fn feature_levels() {
+        let relative = Command::IoWrite(AddressingMode::Relative(1));
+        let immediate =
+            Command::JmpIfTrue(AddressingMode::Immediate(1), AddressingMode::Register(0));
+        assert_eq!(Profile::Day9.check(&relative), Ok(()));
+        assert_eq!(
+            Profile::Day5.check(&relative),
+            Err(MachineErrorKind::InvalidAddressingMode {
+                operand: 1,
+                mode: 2
+            })
+        );
+        assert_eq!(Profile::Day5.check(&immediate), Ok(()));
+        assert_eq!(
+            Profile::Day2.check(&immediate),
+            Err(MachineErrorKind::UnknownOpcode(5))
+        );
+        assert_eq!("day5".parse(), Ok(Profile::Day5));
+        assert!("day7".parse::<Profile>().is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use super::instruction::Command;
+use super::machine::{ExecutionState, Machine, MachineError};
+use super::trace::{TraceEvent, Tracer};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+// One value read or written by the machine. `instruction` counts the instructions executed
+// before this one, so it identifies the point in the run the value was moved at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub instruction: u64,
+    pub elapsed: Duration,
+    pub direction: Direction,
+    pub value: i64,
+}
+
+impl Entry {
+    // Whether two entries are the same apart from when they happened.
+    pub fn matches(&self, other: &Entry) -> bool {
+        (self.instruction, self.direction, self.value)
+            == (other.instruction, other.direction, other.value)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    // `(address, value)` writes made to the program before it ran, such as day 2's noun and
+    // verb, which replaying makes again.
+    pub patches: Vec<(usize, i64)>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    Machine(MachineError),
+    // The first entry that differs. None means one run stopped before the other.
+    Diverged {
+        index: usize,
+        expected: Option<Entry>,
+        found: Option<Entry>,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let show = |entry: &Option<Entry>| match entry {
+            Some(entry) => entry.to_string(),
+            None => String::from("end of run"),
+        };
+        match self {
+            ReplayError::Machine(error) => write!(f, "{}", error),
+            ReplayError::Diverged {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "entry {} differs: expected '{}', found '{}'",
+                index,
+                show(expected),
+                show(found)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Machine(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Version 1 files are the same without patches.
+static HEADER: &str = "intcode-recording 2";
+static HEADER_V1: &str = "intcode-recording 1";
+
+impl Recording {
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn _values(&self, direction: Direction) -> Vec<i64> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.direction == direction)
+            .map(|entry| entry.value)
+            .collect()
+    }
+
+    pub fn inputs(&self) -> Vec<i64> {
+        self._values(Direction::Input)
+    }
+
+    pub fn outputs(&self) -> Vec<i64> {
+        self._values(Direction::Output)
+    }
+
+    // Compares two runs, ignoring timestamps.
+    pub fn diverges(&self, other: &Recording) -> Option<ReplayError> {
+        let length = self.entries.len().max(other.entries.len());
+        (0..length)
+            .map(|index| (index, self.entries.get(index), other.entries.get(index)))
+            .find(|(_, expected, found)| match (expected, found) {
+                (Some(expected), Some(found)) => !expected.matches(found),
+                _ => true,
+            })
+            .map(|(index, expected, found)| ReplayError::Diverged {
+                index,
+                expected: expected.copied(),
+                found: found.copied(),
+            })
+    }
+
+    // Runs `program` again with the recorded patches and inputs until it halts or runs out of
+    // input, recording the new run.
+    pub fn replay(&self, program: Vec<i64>) -> Result<Recording, MachineError> {
+        let recorder = Arc::new(Mutex::new(Recorder::new().with_patches(&self.patches)));
+        let inputs: VecDeque<i64> = self.inputs().into_iter().collect();
+        let mut machine = Machine::new(program, inputs, Vec::new()).with_tracer(recorder.clone());
+        for (address, value) in &self.patches {
+            machine.write_address(*address, *value)?;
+        }
+        while let ExecutionState::Output(_) = machine.run_until_io()? {}
+        drop(machine);
+        let recorder = recorder.lock().expect("tracer lock poisoned");
+        Ok(recorder.recording().clone())
+    }
+
+    // Checks that `program` still produces exactly the recorded run.
+    pub fn verify(&self, program: Vec<i64>) -> Result<(), ReplayError> {
+        let replayed = self.replay(program).map_err(ReplayError::Machine)?;
+        match self.diverges(&replayed) {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Input => "in",
+            Direction::Output => "out",
+        };
+        write!(
+            f,
+            "{} {} {} {}",
+            self.instruction,
+            self.elapsed.as_micros(),
+            direction,
+            self.value
+        )
+    }
+}
+
+fn _number<T>(word: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    word.parse()
+        .map_err(|e| format!("bad number '{}': {}", word, e))
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    // `instruction elapsed_micros in|out value`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (instruction, elapsed, direction, value) = match words[..] {
+            [instruction, elapsed, direction, value] => (instruction, elapsed, direction, value),
+            _ => return Err(format!("expected 4 fields in '{}'", s)),
+        };
+        let direction = match direction {
+            "in" => Direction::Input,
+            "out" => Direction::Output,
+            other => return Err(format!("expected in or out, found '{}'", other)),
+        };
+        Ok(Entry {
+            instruction: _number(instruction)?,
+            elapsed: Duration::from_micros(_number(elapsed)?),
+            direction,
+            value: _number(value)?,
+        })
+    }
+}
+
+// The file format is a header line, a `patch address value` line for each patch and then one
+// entry per line.
+impl std::fmt::Display for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (address, value) in &self.patches {
+            writeln!(f, "patch {} {}", address, value)?;
+        }
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty()).peekable();
+        if !matches!(lines.next(), Some(header) if header == HEADER || header == HEADER_V1) {
+            return Err(String::from("not a recording file"));
+        }
+        let mut patches = Vec::new();
+        while let Some(line) = lines.next_if(|line| line.starts_with("patch")) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                ["patch", address, value] => patches.push((_number(address)?, _number(value)?)),
+                _ => return Err(format!("expected 'patch address value', found '{}'", line)),
+            }
+        }
+        let entries = lines
+            .enumerate()
+            .map(|(number, line)| {
+                line.parse()
+                    .map_err(|e| format!("entry {}: {}", number + 1, e))
+            })
+            .collect::<Result<Vec<Entry>, String>>()?;
+        Ok(Recording { patches, entries })
+    }
+}
+
+// A tracer that records the values a machine reads and writes.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    // set by the first traced instruction, so time spent setting up isn't recorded.
+    start: Option<Instant>,
+    instructions: u64,
+    recording: Recording,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder {
+            start: None,
+            instructions: 0,
+            recording: Recording::default(),
+        }
+    }
+
+    // Notes writes made to the program before it ran, so that a replay can make them too.
+    pub fn with_patches(mut self, patches: &[(usize, i64)]) -> Self {
+        self.recording.patches = patches.to_vec();
+        self
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+}
+
+impl Tracer for Recorder {
+    fn trace(&mut self, event: &TraceEvent) {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let moved = match (event.command, event.write, event.values.first()) {
+            (Command::IoRead(_), Some((_, value)), _) => Some((Direction::Input, value)),
+            (Command::IoWrite(_), _, Some(value)) => Some((Direction::Output, *value)),
+            _ => None,
+        };
+        if let Some((direction, value)) = moved {
+            self.recording.entries.push(Entry {
+                instruction: self.instructions,
+                // whole microseconds, which is what the file keeps.
+                elapsed: Duration::from_micros(start.elapsed().as_micros() as u64),
+                direction,
+                value,
+            });
+        }
+        self.instructions += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::*;
+
+    // outputs each input multiplied by `factor` until it reads 0.
+    fn multiplier(factor: i64) -> Vec<i64> {
+        assemble(&format!(
+            "
+            loop:   in [n]
+                    jf [n], #end
+                    mul [n], #{}, [n]
+                    out [n]
+                    jt #1, #loop
+            end:    hlt
+            n:      data 0
+            ",
+            factor
+        ))
+        .expect("failed to assemble")
+    }
+
+    fn record(program: Vec<i64>, inputs: Vec<i64>) -> Recording {
+        let recorder = Arc::new(Mutex::new(Recorder::new()));
+        let mut machine = Machine::new(program, inputs, Vec::new()).with_tracer(recorder.clone());
+        machine.execute().expect("failed to execute");
+        drop(machine);
+        let recorder = recorder.lock().expect("tracer lock poisoned");
+        recorder.recording().clone()
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let recording = record(multiplier(2), vec![3, 5, 0]);
+        assert_eq!(recording.inputs(), vec![3, 5, 0]);
+        assert_eq!(recording.outputs(), vec![6, 10]);
+        let instructions: Vec<u64> = recording.entries.iter().map(|e| e.instruction).collect();
+        assert_eq!(instructions, vec![0, 3, 5, 8, 10]);
+        assert_eq!(recording.verify(multiplier(2)), Ok(()));
+
+        let path = std::env::temp_dir().join(format!("recording-{}", std::process::id()));
+        let path = path.to_str().expect("temp dir is utf8");
+        recording.save(path).expect("failed to save");
+        assert_eq!(Recording::load(path).expect("failed to load"), recording);
+        fs::remove_file(path).expect("failed to clean up");
+    }
+
+    #[test]
+    fn patched_replay() {
+        // the factor is patched from 2 to 4 before the run.
+        let mut program = multiplier(2);
+        let factor = program
+            .iter()
+            .position(|value| *value == 2)
+            .expect("no factor");
+        program[factor] = 4;
+        let recorder = Arc::new(Mutex::new(Recorder::new().with_patches(&[(factor, 4)])));
+        let mut machine =
+            Machine::new(program, vec![3, 0], Vec::new()).with_tracer(recorder.clone());
+        machine.execute().expect("failed to execute");
+        drop(machine);
+        let recording = recorder
+            .lock()
+            .expect("tracer lock poisoned")
+            .recording()
+            .clone();
+        assert_eq!(recording.outputs(), vec![12]);
+        assert_eq!(recording.verify(multiplier(2)), Ok(()));
+
+        let text = recording.to_string();
+        assert!(text.starts_with(&format!("intcode-recording 2\npatch {} 4\n", factor)));
+        assert_eq!(text.parse(), Ok(recording));
+        let unpatched = Recording {
+            patches: Vec::new(),
+            ..text.parse().expect("failed to parse")
+        };
+        assert!(unpatched.verify(multiplier(2)).is_err());
+    }
+
+    #[test]
+    fn divergence() {
+        let recording = record(multiplier(2), vec![3, 0]);
+        match recording.verify(multiplier(3)) {
+            Err(ReplayError::Diverged {
+                index: 1,
+                expected: Some(expected),
+                found: Some(found),
+            }) => assert_eq!((expected.value, found.value), (6, 9)),
+            other => panic!("expected a divergence, found {:?}", other),
+        }
+        let mut short = recording.clone();
+        let last = short.entries.pop();
+        assert_eq!(
+            recording.diverges(&short),
+            Some(ReplayError::Diverged {
+                index: 2,
+                expected: last,
+                found: None
+            })
+        );
+    }
+
+    #[test]
+    fn bad_recordings() {
+        assert!("0 0 in 1".parse::<Recording>().is_err());
+        assert!("intcode-recording 1\n0 0 sideways 1"
+            .parse::<Recording>()
+            .is_err());
+        assert!("intcode-recording 1\n0 in 1".parse::<Recording>().is_err());
+        assert!("intcode-recording 2\npatch 1\n0 0 in 1"
+            .parse::<Recording>()
+            .is_err());
+        assert_eq!(
+            "intcode-recording 1\n0 0 in 1"
+                .parse::<Recording>()
+                .map(|recording| recording.patches),
+            Ok(Vec::new())
+        );
+        // steps and times can't be negative.
+        assert!("-1 0 in 1".parse::<Entry>().is_err());
+        assert!("0 -1 in 1".parse::<Entry>().is_err());
+        assert!("0 0 in -1".parse::<Entry>().is_ok());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+// The state of a machine between instructions. I/O is not included, so a snapshot can be
+// restored into a machine with different input and output.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    pub memory: Vec<i64>,
+    pub memory_limit: usize,
+    pub program_counter: usize,
+    pub relative_base: i64,
+}
+
+static HEADER: &str = "intcode-snapshot 1";
+
+impl Snapshot {
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// The file format is a header line, one `name value` line per register and the memory as a
+// comma separated line in the same format as the puzzle inputs.
+impl std::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "program_counter {}", self.program_counter)?;
+        writeln!(f, "relative_base {}", self.relative_base)?;
+        writeln!(f, "memory_limit {}", self.memory_limit)?;
+        let memory: Vec<String> = self.memory.iter().map(|v| v.to_string()).collect();
+        writeln!(f, "{}", memory.join(","))
+    }
+}
+
+// Parses the next line as the register `name`, as whichever type the register has.
+fn _field<T>(lines: &mut std::str::Lines, name: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let line = lines.next().ok_or(format!("missing {}", name))?;
+    match line.split_once(' ') {
+        Some((key, value)) if key == name => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("bad {}: {}", name, e)),
+        _ => Err(format!("expected {}, found '{}'", name, line)),
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(String::from("not a snapshot file"));
+        }
+        let program_counter = _field(&mut lines, "program_counter")?;
+        let relative_base = _field(&mut lines, "relative_base")?;
+        let memory_limit = _field(&mut lines, "memory_limit")?;
+        let memory = match lines.next().map(str::trim) {
+            None | Some("") => Vec::new(),
+            Some(line) => line
+                .split(',')
+                .map(|v| v.trim().parse().map_err(|e| format!("bad memory: {}", e)))
+                .collect::<Result<Vec<i64>, String>>()?,
+        };
+        Ok(Snapshot {
+            memory,
+            memory_limit,
+            program_counter,
+            relative_base,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let snapshot = Snapshot {
+            memory: vec![109, -1, 204, 1, 99],
+            memory_limit: 4096,
+            program_counter: 2,
+            relative_base: -1,
+        };
+        let path = std::env::temp_dir().join(format!("snapshot-{}", std::process::id()));
+        let path = path.to_str().expect("temp dir is utf8");
+        snapshot.save(path).expect("failed to save");
+        assert_eq!(Snapshot::load(path).expect("failed to load"), snapshot);
+        fs::remove_file(path).expect("failed to clean up");
+    }
+
+    #[test]
+    fn bad_snapshots() {
+        assert!("1,2,3".parse::<Snapshot>().is_err());
+        assert!("intcode-snapshot 1\nprogram_counter x\n"
+            .parse::<Snapshot>()
+            .is_err());
+        assert!(
+            "intcode-snapshot 1\nrelative_base 0\nprogram_counter 0\nmemory_limit 1\n1"
+                .parse::<Snapshot>()
+                .is_err()
+        );
+        let negative = "intcode-snapshot 1\nprogram_counter -1\nrelative_base 0\nmemory_limit 1\n1";
+        assert!(negative.parse::<Snapshot>().is_err());
+        let negative =
+            "intcode-snapshot 1\nprogram_counter 0\nrelative_base -1\nmemory_limit -1\n1";
+        assert_eq!(
+            negative.parse::<Snapshot>(),
+            Err(String::from(
+                "bad memory_limit: invalid digit found in string"
+            ))
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use super::instruction::Command;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+// One executed instruction. `values` holds each operand as the instruction saw it: the value
+// read for inputs to the instruction and the resolved address for the operand written to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub program_counter: usize,
+    pub relative_base: i64,
+    pub command: Command,
+    pub values: Vec<i64>,
+    pub write: Option<(usize, i64)>,
+}
+
+impl std::fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>5}: {:<24} {:?}",
+            self.program_counter,
+            self.command.to_string(),
+            self.values
+        )?;
+        if let Some((address, value)) = self.write {
+            write!(f, " [{}] <- {}", address, value)?;
+        }
+        Ok(())
+    }
+}
+
+pub trait Tracer {
+    fn trace(&mut self, event: &TraceEvent);
+}
+
+impl Tracer for Vec<TraceEvent> {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.push(event.clone());
+    }
+}
+
+impl Tracer for Sender<TraceEvent> {
+    fn trace(&mut self, event: &TraceEvent) {
+        // tracing shouldn't stop the machine, so a closed receiver is ignored.
+        let _ = self.send(event.clone());
+    }
+}
+
+// Lets the caller keep a handle on a tracer that has been given to a machine.
+impl<T: Tracer> Tracer for Arc<Mutex<T>> {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.lock().expect("tracer lock poisoned").trace(event);
+    }
+}
+
+// Sends each event to both tracers.
+impl<A: Tracer, B: Tracer> Tracer for (A, B) {
+    fn trace(&mut self, event: &TraceEvent) {
+        self.0.trace(event);
+        self.1.trace(event);
+    }
+}
+
+// Writes one line per instruction.
+#[derive(Debug)]
+pub struct TraceWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(writer: W) -> Self {
+        TraceWriter { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Tracer for TraceWriter<W> {
+    fn trace(&mut self, event: &TraceEvent) {
+        let _ = writeln!(self.writer, "{}", event);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profiler {
+    hits: BTreeMap<usize, u64>,
+    opcodes: BTreeMap<&'static str, u64>,
+    instructions: u64,
+    inputs: u64,
+    outputs: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn inputs(&self) -> u64 {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> u64 {
+        self.outputs
+    }
+
+    pub fn hits(&self, address: usize) -> u64 {
+        self.hits.get(&address).copied().unwrap_or(0)
+    }
+
+    pub fn opcode_histogram(&self) -> &BTreeMap<&'static str, u64> {
+        &self.opcodes
+    }
+
+    // The `count` most executed addresses, most executed first.
+    pub fn hottest(&self, count: usize) -> Vec<(usize, u64)> {
+        let mut hits: Vec<(usize, u64)> = self.hits.iter().map(|(a, h)| (*a, *h)).collect();
+        hits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits.truncate(count);
+        hits
+    }
+}
+
+impl Tracer for Profiler {
+    fn trace(&mut self, event: &TraceEvent) {
+        *self.hits.entry(event.program_counter).or_insert(0) += 1;
+        *self.opcodes.entry(event.command.mnemonic()).or_insert(0) += 1;
+        self.instructions += 1;
+        match event.command {
+            Command::IoRead(_) => self.inputs += 1,
+            Command::IoWrite(_) => self.outputs += 1,
+            _ => {}
+        }
+    }
+}
+
+impl std::fmt::Display for Profiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "instructions: {}", self.instructions)?;
+        writeln!(f, "inputs: {} outputs: {}", self.inputs, self.outputs)?;
+        writeln!(f, "opcodes:")?;
+        for (mnemonic, count) in &self.opcodes {
+            writeln!(f, "  {:<4} {}", mnemonic, count)?;
+        }
+        writeln!(f, "hottest addresses:")?;
+        for (address, count) in self.hottest(10) {
+            writeln!(f, "  {:>5} {}", address, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::machine::Machine;
+    use super::*;
+
+    #[test]
+    fn trace_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut machine = Machine::with_program(vec![3, 9, 1001, 9, 1, 9, 4, 9, 99, 0])
+            .with_tracer(events.clone());
+        machine.push_input(41);
+        machine.execute().expect("failed to execute");
+        let lines: Vec<String> = events
+            .lock()
+            .expect("tracer lock poisoned")
+            .iter()
+            .map(|event| event.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "    0: in [9]                   [9] [9] <- 41",
+                "    2: add [9], #1, [9]         [41, 1, 9] [9] <- 42",
+                "    6: out [9]                  [42]",
+                "    8: hlt                      []",
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_loop() {
+        // counts down from 3, outputting each value.
+        let program = vec![1101, 3, 0, 14, 4, 14, 1001, 14, -1, 14, 1005, 14, 4, 99, 0];
+        let profiler = Arc::new(Mutex::new(Profiler::new()));
+        let mut machine = Machine::with_program(program).with_tracer(profiler.clone());
+        machine.execute().expect("failed to execute");
+        let profiler = profiler.lock().expect("tracer lock poisoned");
+        assert_eq!(machine.output(), &vec![3, 2, 1]);
+        assert_eq!(profiler.instructions(), 1 + 3 * 3 + 1);
+        assert_eq!(profiler.outputs(), 3);
+        assert_eq!(profiler.inputs(), 0);
+        assert_eq!(profiler.hits(4), 3);
+        assert_eq!(profiler.opcode_histogram()["jt"], 3);
+        assert_eq!(profiler.hottest(1), vec![(4, 3)]);
+    }
+}
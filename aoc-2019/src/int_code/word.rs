@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::num::Wrapping;
+use std::str::FromStr;
+
+// The values a machine's memory holds, which decide what happens when arithmetic overflows:
+//
+//     i64             overflow fails the instruction with `MachineErrorKind::Overflow`
+//     Wrapping<i64>   overflow wraps around, as release builds did before
+//     BigInt          never overflows
+//
+// Addresses, opcodes and the relative base are always i64, so a word that doesn't fit is an
+// error wherever one of those is needed.
+pub trait Word: Clone + Debug + Default + Display + Ord + Hash + Send + 'static {
+    fn from_i64(value: i64) -> Self;
+    // None if the value doesn't fit in an i64.
+    fn to_i64(&self) -> Option<i64>;
+    // None if the result can't be represented.
+    fn try_add(&self, other: &Self) -> Option<Self>;
+    fn try_mul(&self, other: &Self) -> Option<Self>;
+}
+
+impl Word for i64 {
+    fn from_i64(value: i64) -> Self {
+        value
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(*other)
+    }
+}
+
+impl Word for Wrapping<i64> {
+    fn from_i64(value: i64) -> Self {
+        Wrapping(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(self.0)
+    }
+
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+// The nearest i64 to `word`, for reporting values in errors and traces.
+pub fn clamp<W: Word>(word: &W) -> i64 {
+    match word.to_i64() {
+        Some(value) => value,
+        None if *word < W::default() => i64::MIN,
+        None => i64::MAX,
+    }
+}
+
+// Converts a program loaded as i64s.
+pub fn words<W: Word>(program: &[i64]) -> Vec<W> {
+    program.iter().map(|value| W::from_i64(*value)).collect()
+}
+
+// A signed integer of any size: a sign and base 2^32 digits, least significant first, with
+// no leading zero digits. Zero is never negative, so equal values compare and hash equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+fn _compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn _add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let digit = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(digit as u32);
+        carry = digit >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+// `a - b` where `a >= b`.
+fn _subtract_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, digit) in a.iter().enumerate() {
+        let mut digit = *digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if digit < 0 {
+            digit += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(digit as u32);
+    }
+    difference
+}
+
+fn _multiply_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let digit = product[i + j] as u64 + *x as u64 * *y as u64 + carry;
+            product[i + j] = digit as u32;
+            carry = digit >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+impl BigInt {
+    fn _new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::_new(
+                self.negative,
+                _add_magnitudes(&self.magnitude, &other.magnitude),
+            );
+        }
+        match _compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::_new(
+                other.negative,
+                _subtract_magnitudes(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::_new(
+                self.negative,
+                _subtract_magnitudes(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::_new(
+            self.negative != other.negative,
+            _multiply_magnitudes(&self.magnitude, &other.magnitude),
+        )
+    }
+
+    // Divides the magnitude by `divisor` in place, returning the remainder.
+    fn _divide_magnitude(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for digit in magnitude.iter_mut().rev() {
+            let value = (remainder << 32) | *digit as u64;
+            *digit = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        remainder as u32
+    }
+}
+
+impl Word for BigInt {
+    fn from_i64(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::_new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |value, digit| (value << 32) | *digit as u64);
+        match self.negative {
+            true if magnitude <= 1 << 63 => Some((magnitude as i64).wrapping_neg()),
+            false if magnitude <= i64::MAX as u64 => Some(magnitude as i64),
+            _ => None,
+        }
+    }
+
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        Some(self.add(other))
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        Some(self.mul(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => _compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => _compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // nine decimal digits at a time, least significant first.
+        let mut magnitude = self.magnitude.clone();
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(BigInt::_divide_magnitude(&mut magnitude, 1_000_000_000));
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match chunks.pop() {
+            Some(first) => write!(f, "{}", first)?,
+            None => write!(f, "0")?,
+        }
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("'{}' is not an integer", s));
+        }
+        let ten = BigInt::from_i64(10);
+        let value = digits.bytes().fold(BigInt::default(), |value, digit| {
+            value
+                .mul(&ten)
+                .add(&BigInt::from_i64((digit - b'0') as i64))
+        });
+        Ok(BigInt::_new(negative, value.magnitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().expect("failed to parse")
+    }
+
+    #[test]
+    fn big_arithmetic() {
+        let max = BigInt::from_i64(i64::MAX);
+        let min = BigInt::from_i64(i64::MIN);
+        assert_eq!(max.to_i64(), Some(i64::MAX));
+        assert_eq!(min.to_i64(), Some(i64::MIN));
+        assert_eq!(max.add(&BigInt::from_i64(1)).to_i64(), None);
+        assert_eq!(min.add(&max).to_i64(), Some(-1));
+        assert_eq!(
+            max.mul(&max).to_string(),
+            "85070591730234615847396907784232501249"
+        );
+        assert_eq!(
+            min.mul(&max),
+            big("-85070591730234615856620279821087277056")
+        );
+        assert_eq!(big("-0"), BigInt::default());
+        assert_eq!(BigInt::default().to_string(), "0");
+        assert_eq!(big("1000000000").to_string(), "1000000000");
+        assert_eq!(big("-5").add(&big("3")).to_string(), "-2");
+        assert_eq!(big("5").add(&big("-5")), BigInt::default());
+        assert!(big("-99999999999999999999") < min);
+        assert!("12a".parse::<BigInt>().is_err());
+        assert!("".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn overflow_behaviour() {
+        assert_eq!(i64::MAX.try_add(&1), None);
+        assert_eq!(i64::MIN.try_mul(&-1), None);
+        assert_eq!(
+            Wrapping(i64::MAX).try_add(&Wrapping(1)),
+            Some(Wrapping(i64::MIN))
+        );
+        assert_eq!(clamp(&big("-99999999999999999999")), i64::MIN);
+        assert_eq!(clamp(&big("99999999999999999999")), i64::MAX);
+        assert_eq!(words::<BigInt>(&[1, -2])[1], big("-2"));
+    }
+}